@@ -7,25 +7,368 @@
 //! Cargo re-run the build script whenever `memory.x` is changed,
 //! updating `memory.x` ensures a rebuild of the application with the
 //! new memory settings.
+//!
+//! With the `alloc` feature, a `.heap` region of `HEAP_SIZE` bytes is also
+//! reserved in RAM for the global allocator. By default it takes whatever the
+//! scratch arena leaves, 64K with the arena in `capacity.toml`; the two are
+//! both static, so the build stops if they cannot fit together.
+//!
+//! The last 16K of flash is kept back from the application for the results
+//! log. With the `firmware-partition` feature, the application is kept to
+//! the first 1M of flash and the cyw43 firmware and CLM blobs get partitions
+//! of their own above it, written by `cargo xtask flash-firmware`.
+//!
+//! The short hash of the commit being built, if any, is passed on as
+//! `AOC_BUILD_ID`, to tell apart the builds that wrote the results log.
+//!
+//! The per-day container capacities in `capacity.toml` (or the file named by
+//! `CAPACITY_CONFIG`) become the constants of `crate::aoc::config`.
+//!
+//! Each day declared in `src/aoc/mod.rs` whose `input/dayN.sample` and
+//! `input/dayN.full` both exist gets an `input = "dayN"` cfg, so days without
+//! inputs are left out rather than failing the build. Those days make up the
+//! registry in `crate::aoc::days`, together with the answers expected of them
+//! from `input/answers.toml`. With the `compress-inputs` feature, the inputs
+//! the registry embeds are packed first, see `src/aoc/utils/unpack.rs`.
 
 use std::env;
+use std::fmt::Write as _;
 use std::fs::File;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+#[path = "build/pack.rs"]
+mod pack;
+
+/// RAM in `memory.x`.
+const RAM: usize = 256 * 1024;
+/// RAM kept back from the scratch arena and heap for the rest of the
+/// firmware: the executor's 32K task arena, the other statics and the stacks.
+const RESERVED: usize = 64 * 1024;
 
 fn main() {
     // Put `memory.x` in our output directory and ensure it's
     // on the linker search path.
     let out = &PathBuf::from(env::var_os("OUT_DIR").unwrap());
-    File::create(out.join("memory.x"))
-        .unwrap()
-        .write_all(include_bytes!("memory.x"))
+    let mut memory = File::create(out.join("memory.x")).unwrap();
+    let partitioned = env::var_os("CARGO_FEATURE_FIRMWARE_PARTITION").is_some();
+    let layout = include_str!("memory.x");
+    let application = "FLASH : ORIGIN = 0x10000100, LENGTH = 2048K - 0x100";
+    assert!(layout.contains(application), "memory.x FLASH region moved");
+    let length = if partitioned {
+        "1024K - 0x100"
+    } else {
+        "2048K - 0x100 - 16K"
+    };
+    let layout = layout.replace(
+        application,
+        &format!("FLASH : ORIGIN = 0x10000100, LENGTH = {length}"),
+    );
+    write!(
+        memory,
+        r#"{layout}
+
+/* ### Results log, see src/results_log.rs */
+MEMORY {{
+    RESULTS_LOG : ORIGIN = 0x101FC000, LENGTH = 16K
+}}
+
+__results_log_start = ORIGIN(RESULTS_LOG);
+__results_log_end = ORIGIN(RESULTS_LOG) + LENGTH(RESULTS_LOG);
+"#
+    )
+    .unwrap();
+    if partitioned {
+        write!(
+            memory,
+            r#"
+/* ### cyw43 firmware partitions, see src/firmware.rs */
+MEMORY {{
+    CYW43_FW  : ORIGIN = 0x10100000, LENGTH = 256K
+    CYW43_CLM : ORIGIN = 0x10140000, LENGTH = 16K
+}}
+
+__cyw43_fw_start = ORIGIN(CYW43_FW);
+__cyw43_fw_end = ORIGIN(CYW43_FW) + LENGTH(CYW43_FW);
+__cyw43_clm_start = ORIGIN(CYW43_CLM);
+__cyw43_clm_end = ORIGIN(CYW43_CLM) + LENGTH(CYW43_CLM);
+"#
+        )
+        .unwrap();
+    }
+    let scratch = write_capacities(out);
+    let pico = env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("none");
+    let spare = (RAM - RESERVED).checked_sub(scratch);
+    if pico && spare.is_none() {
+        panic!(
+            "[task] scratch of {scratch} bytes leaves too little RAM for the rest of the \
+             firmware; at most {} fit",
+            RAM - RESERVED
+        );
+    }
+    if env::var_os("CARGO_FEATURE_ALLOC").is_some() {
+        let spare = spare.unwrap_or(0);
+        let heap_size = env::var("HEAP_SIZE").map_or(spare, |size| parse_size(&size));
+        if pico && (heap_size == 0 || heap_size > spare) {
+            panic!(
+                "HEAP_SIZE of {heap_size} bytes and [task] scratch of {scratch} bytes leave \
+                 too little RAM for the rest of the firmware; at most {spare} bytes of heap fit"
+            );
+        }
+        write!(
+            memory,
+            r#"
+
+/* ### Heap for the global allocator, see src/heap.rs */
+SECTIONS {{
+    .heap (NOLOAD) : ALIGN(8)
+    {{
+        _heap_start = .;
+        . += {heap_size};
+        _heap_end = .;
+    }} > RAM
+}} INSERT AFTER .uninit;
+"#
+        )
         .unwrap();
+    }
     println!("cargo:rustc-link-search={}", out.display());
+    // `linuxmain` is its own entry point, so libc is not started, only
+    // linked for `memcpy` and friends
+    if env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("linux") {
+        println!("cargo:rustc-link-arg-bins=-nostartfiles");
+        println!("cargo:rustc-link-arg-bins=-lc");
+    }
+
+    write_days(out);
+    write_build_id();
 
     // By default, Cargo will re-run a build script whenever
     // any file in the project changes. By specifying `memory.x`
     // here, we ensure the build script is only re-run when
     // `memory.x` is changed.
     println!("cargo:rerun-if-changed=memory.x");
+    println!("cargo:rerun-if-env-changed=HEAP_SIZE");
+}
+
+/// Sets `AOC_BUILD_ID` to the first eight hex digits of the commit, or
+/// zeros outside a git checkout.
+fn write_build_id() {
+    let commit = std::process::Command::new("git")
+        .args(["rev-parse", "--short=8", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok());
+    let id = commit.map_or_else(|| "00000000".into(), |c| c.trim()[..8].to_string());
+    println!("cargo:rustc-env=AOC_BUILD_ID={id}");
+    if let Ok(head) = std::fs::read_to_string(".git/HEAD") {
+        println!("cargo:rerun-if-changed=.git/HEAD");
+        if let Some(branch) = head.trim().strip_prefix("ref: ") {
+            if Path::new(".git").join(branch).exists() {
+                println!("cargo:rerun-if-changed=.git/{branch}");
+            }
+        }
+    }
+}
+
+/// Parses a size such as `65536` or `64K`, as the linker would.
+fn parse_size(size: &str) -> usize {
+    let (digits, scale) = if let Some(digits) = size.strip_suffix(['K', 'k']) {
+        (digits, 1024)
+    } else if let Some(digits) = size.strip_suffix(['M', 'm']) {
+        (digits, 1024 * 1024)
+    } else {
+        (size, 1)
+    };
+    digits
+        .parse::<usize>()
+        .map(|n| n * scale)
+        .unwrap_or_else(|_| panic!("HEAP_SIZE `{size}` should be a byte count, such as 64K"))
+}
+
+/// Turns each `[dayN]` table of the capacity config into a `dayN` module of
+/// `usize` constants, so `grid = 200` becomes `pub const GRID: usize = 200;`,
+/// and returns the scratch arena's size.
+fn write_capacities(out: &Path) -> usize {
+    let config = env::var("CAPACITY_CONFIG").unwrap_or_else(|_| "capacity.toml".into());
+    println!("cargo:rerun-if-changed={config}");
+    println!("cargo:rerun-if-env-changed=CAPACITY_CONFIG");
+
+    let contents = std::fs::read_to_string(&config)
+        .unwrap_or_else(|e| panic!("reading capacity config {config}: {e}"));
+    let document = contents
+        .parse::<toml_edit::DocumentMut>()
+        .unwrap_or_else(|e| panic!("parsing capacity config {config}: {e}"));
+
+    let mut generated = format!("// Generated by build.rs from {config}\n");
+    let mut scratch = 0;
+    for (section, item) in document.iter() {
+        let table = item
+            .as_table()
+            .unwrap_or_else(|| panic!("{config}: `{section}` should be a table"));
+        writeln!(generated, "\npub mod {section} {{").unwrap();
+        for (name, value) in table.iter() {
+            let capacity = value
+                .as_integer()
+                .and_then(|v| usize::try_from(v).ok())
+                .unwrap_or_else(|| {
+                    panic!("{config}: `{section}.{name}` should be a non-negative integer")
+                });
+            writeln!(
+                generated,
+                "    pub const {}: usize = {capacity};",
+                name.to_uppercase()
+            )
+            .unwrap();
+            if (section, name) == ("task", "scratch") {
+                scratch = capacity;
+            }
+        }
+        generated.push_str("}\n");
+    }
+    std::fs::write(out.join("config.rs"), generated).unwrap();
+    scratch
+}
+
+/// Finds the days that can be built and writes the registry that runs them.
+fn write_days(out: &Path) {
+    println!("cargo:rerun-if-changed=input");
+    println!("cargo:rerun-if-changed=src/aoc/mod.rs");
+    println!("cargo:rerun-if-env-changed=AOC_DAY");
+    println!("cargo:rerun-if-env-changed=AOC_BENCH");
+    let names = (1..=25)
+        .map(|day| format!("\"day{day}\""))
+        .collect::<Vec<_>>();
+    println!(
+        "cargo:rustc-check-cfg=cfg(input, values({}))",
+        names.join(", ")
+    );
+
+    let declared = std::fs::read_to_string("src/aoc/mod.rs").unwrap();
+    let days = (1..=25_u8)
+        .filter(|day| declared.contains(&format!("mod day{day};")))
+        .filter(|day| {
+            ["sample", "full"].iter().all(|kind| {
+                let path = format!("input/day{day}.{kind}");
+                println!("cargo:rerun-if-changed={path}");
+                Path::new(&path).exists()
+            })
+        })
+        .collect::<Vec<_>>();
+    for day in days.iter() {
+        println!("cargo:rustc-cfg=input=\"day{day}\"");
+    }
+
+    let selected = match env::var("AOC_DAY").ok().filter(|day| !day.is_empty()) {
+        Some(day) => {
+            let day = day
+                .trim_start_matches("day")
+                .parse::<u8>()
+                .unwrap_or_else(|_| panic!("AOC_DAY should be a day number, not {day}"));
+            if !days.contains(&day) {
+                println!("cargo:warning=AOC_DAY={day} has no inputs in input/");
+            }
+            format!("Some({day})")
+        }
+        None => "None".into(),
+    };
+
+    let bench = match env::var("AOC_BENCH").ok().filter(|runs| !runs.is_empty()) {
+        Some(runs) => match runs.parse::<usize>() {
+            Ok(runs) if runs > 0 => format!("Some({runs})"),
+            _ => panic!("AOC_BENCH should be a number of runs, not {runs}"),
+        },
+        None => "None".into(),
+    };
+
+    let mut generated = String::from("// Generated by build.rs from input/ and src/aoc/mod.rs\n\n");
+    writeln!(generated, "pub const DAYS: &[u8] = &{days:?};").unwrap();
+    writeln!(generated, "pub const SELECTED: Option<u8> = {selected};").unwrap();
+    writeln!(generated, "pub const BENCH: Option<usize> = {bench};").unwrap();
+    generated.push_str(
+        "\npub fn run(task: &mut AocTask, day: u8, input: Input) -> bool {\n    match day {\n",
+    );
+    for day in days.iter() {
+        let [sample, full] = ["sample", "full"].map(|kind| embedded(out, *day, kind));
+        writeln!(
+            generated,
+            "        {day} => task.run_solution::<super::day{day}::Solution>(\n            \
+             {day},\n            \
+             &Inputs {{\n                \
+             sample: include_bytes!({sample:?}),\n                \
+             full: include_bytes!({full:?}),\n            \
+             }},\n            \
+             input,\n        \
+             ),"
+        )
+        .unwrap();
+    }
+    generated.push_str("        _ => return false,\n    }\n    true\n}\n");
+
+    generated.push_str("\npub const ANSWERS: &[Answer] = &[\n");
+    for (day, input, parts) in answers(&days) {
+        writeln!(
+            generated,
+            "    Answer {{ day: {day}, input: {input:?}, parts: &{parts:?} }},"
+        )
+        .unwrap();
+    }
+    generated.push_str("];\n");
+    std::fs::write(out.join("days.rs"), generated).unwrap();
+}
+
+/// Path of the file to embed for one of a day's inputs: the input itself, or
+/// a packed copy of it with the `compress-inputs` feature.
+fn embedded(out: &Path, day: u8, kind: &str) -> PathBuf {
+    let path = Path::new(&env::var_os("CARGO_MANIFEST_DIR").unwrap())
+        .join("input")
+        .join(format!("day{day}.{kind}"));
+    if env::var_os("CARGO_FEATURE_COMPRESS_INPUTS").is_none() {
+        return path;
+    }
+    let packed_path = out.join(format!("day{day}.{kind}.lz"));
+    std::fs::write(&packed_path, pack::pack(&std::fs::read(&path).unwrap())).unwrap();
+    packed_path
+}
+
+/// Reads `input/answers.toml`, where each `[dayN]` table holds the expected
+/// answers of each input as a list, e.g. `sample = [11, 31]`. Answers may be
+/// numbers or strings.
+fn answers(days: &[u8]) -> Vec<(u8, String, Vec<String>)> {
+    let path = "input/answers.toml";
+    println!("cargo:rerun-if-changed={path}");
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    let document = contents
+        .parse::<toml_edit::DocumentMut>()
+        .unwrap_or_else(|e| panic!("parsing {path}: {e}"));
+
+    let mut answers = Vec::new();
+    for (section, item) in document.iter() {
+        let day = section
+            .strip_prefix("day")
+            .and_then(|day| day.parse::<u8>().ok())
+            .unwrap_or_else(|| panic!("{path}: `{section}` should be named dayN"));
+        if !days.contains(&day) {
+            continue;
+        }
+        let table = item
+            .as_table()
+            .unwrap_or_else(|| panic!("{path}: `{section}` should be a table"));
+        for (input, value) in table.iter() {
+            let parts = value
+                .as_array()
+                .unwrap_or_else(|| panic!("{path}: `{section}.{input}` should be a list"))
+                .iter()
+                .map(|part| match part.as_str() {
+                    Some(text) => text.to_string(),
+                    None => part.to_string().trim().to_string(),
+                })
+                .collect();
+            answers.push((day, input.to_string(), parts));
+        }
+    }
+    answers
 }
@@ -0,0 +1,80 @@
+// The packer behind the `compress-inputs` feature, shared by `build.rs` and
+// the round-trip test in `src/aoc/utils/unpack.rs`.
+
+use std::collections::HashMap;
+
+/// Packs `data` in the format `src/aoc/utils/unpack.rs` decodes: greedy
+/// LZ77 over a 64K window, with earlier positions found through chains of
+/// those starting with the same 3 bytes.
+pub fn pack(data: &[u8]) -> Vec<u8> {
+    const MIN_MATCH: usize = 3;
+    const MAX_MATCH: usize = 0x7F + MIN_MATCH;
+    const MAX_LITERALS: usize = 0x80;
+    const WINDOW: usize = u16::MAX as usize;
+    /// Candidates tried per position, trading ratio for build time
+    const MAX_CHAIN: usize = 256;
+    const NONE: usize = usize::MAX;
+
+    let mut packed = (data.len() as u32).to_le_bytes().to_vec();
+    let flush = |packed: &mut Vec<u8>, literals: &[u8]| {
+        for chunk in literals.chunks(MAX_LITERALS) {
+            packed.push((chunk.len() - 1) as u8);
+            packed.extend_from_slice(chunk);
+        }
+    };
+
+    // Most recent position of each 3-byte prefix, and the one before each
+    let mut head = HashMap::new();
+    let mut previous = vec![NONE; data.len()];
+    fn remember<'a>(
+        data: &'a [u8],
+        pos: usize,
+        head: &mut HashMap<&'a [u8], usize>,
+        previous: &mut [usize],
+    ) {
+        if let Some(key) = data.get(pos..pos + MIN_MATCH) {
+            previous[pos] = head.insert(key, pos).unwrap_or(NONE);
+        }
+    }
+
+    let mut pos = 0;
+    let mut literals = 0;
+    while pos < data.len() {
+        let (mut best_len, mut best_distance) = (0, 0);
+        let mut candidate = data
+            .get(pos..pos + MIN_MATCH)
+            .and_then(|key| head.get(key).copied())
+            .unwrap_or(NONE);
+        for _ in 0..MAX_CHAIN {
+            if candidate == NONE || pos - candidate > WINDOW {
+                break;
+            }
+            let len = data[candidate..]
+                .iter()
+                .zip(data[pos..].iter())
+                .take(MAX_MATCH)
+                .take_while(|(a, b)| a == b)
+                .count();
+            if len > best_len {
+                (best_len, best_distance) = (len, pos - candidate);
+            }
+            candidate = previous[candidate];
+        }
+
+        if best_len < MIN_MATCH {
+            remember(data, pos, &mut head, &mut previous);
+            pos += 1;
+            continue;
+        }
+        flush(&mut packed, &data[literals..pos]);
+        packed.push(0x80 | (best_len - MIN_MATCH) as u8);
+        packed.extend_from_slice(&(best_distance as u16).to_le_bytes());
+        for p in pos..pos + best_len {
+            remember(data, p, &mut head, &mut previous);
+        }
+        pos += best_len;
+        literals = pos;
+    }
+    flush(&mut packed, &data[literals..]);
+    packed
+}
@@ -0,0 +1,93 @@
+//! Benchmarks: a day's full input run over and over, with each run split
+//! into laps at the points the days already mark, parsing ending as the day
+//! moves on to [`Phase::Solving`](super::status::Phase::Solving) and each
+//! part as its answer is recorded. Time a day spends on several of these at
+//! once, such as the line-at-a-time days solving as they read, counts
+//! against the last of them it finishes.
+
+use arrayvec::ArrayVec;
+
+use super::utils::{clock, status, Answers, DayResult};
+use crate::{error, info};
+
+/// Most runs a benchmark keeps timings of.
+const MAX_RUNS: usize = super::config::task::BENCH_RUNS;
+const LAPS: [&str; 3] = ["parse", "part1", "part2"];
+
+/// Runs `run` `runs` times, then logs the statistics of each lap in cycles,
+/// over the first [`MAX_RUNS`] of them.
+pub fn run(day: u8, runs: usize, mut run: impl FnMut(&mut Answers) -> DayResult) -> DayResult {
+    if runs > MAX_RUNS {
+        info!(
+            "day{} bench keeps timings of the first {} of {} runs",
+            day, MAX_RUNS, runs
+        );
+    }
+    let mut laps = ArrayVec::<[u64; 3], MAX_RUNS>::new();
+    for _ in 0..runs {
+        let mut answers = Answers::default();
+        let start = clock::cycles();
+        run(&mut answers)?;
+        let end = clock::cycles();
+        // Stamps from before this run mean the day never got that far
+        let parsed = status::solving_at().max(start);
+        let part1 = answers.recorded_at(1).max(parsed);
+        if !laps.is_full() {
+            laps.push([parsed - start, part1 - parsed, end.saturating_sub(part1)]);
+        }
+    }
+    if laps.is_empty() {
+        error!("day{} bench needs at least one run", day);
+        return Ok(());
+    }
+    for (lap, name) in LAPS.iter().enumerate() {
+        let mut samples = laps
+            .iter()
+            .map(|l| l[lap])
+            .collect::<ArrayVec<_, MAX_RUNS>>();
+        let stats = Stats::of(&mut samples);
+        info!(
+            "day{} bench {}: min {} median {} max {} stddev {} cycles over {} runs",
+            day,
+            name,
+            stats.min,
+            stats.median,
+            stats.max,
+            stats.stddev,
+            laps.len()
+        );
+    }
+    Ok(())
+}
+
+struct Stats {
+    min: u64,
+    median: u64,
+    max: u64,
+    stddev: u64,
+}
+
+impl Stats {
+    /// Of `samples`, which must not be empty, and are sorted along the way.
+    fn of(samples: &mut [u64]) -> Self {
+        samples.sort_unstable();
+        let n = samples.len();
+        let median = if n % 2 == 1 {
+            samples[n / 2]
+        } else {
+            (samples[n / 2 - 1] + samples[n / 2]) / 2
+        };
+        let mean = samples.iter().map(|s| *s as u128).sum::<u128>() / n as u128;
+        let variance = samples
+            .iter()
+            .map(|s| (*s as u128).abs_diff(mean).pow(2))
+            .sum::<u128>()
+            / n as u128;
+        Stats {
+            min: samples[0],
+            median,
+            max: samples[n - 1],
+            stddev: variance.isqrt() as u64,
+        }
+    }
+}
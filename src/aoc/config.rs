@@ -0,0 +1,7 @@
+//! Capacities of each day's fixed-size containers, one module per table of
+//! `capacity.toml` (or the file named by `CAPACITY_CONFIG` at build time).
+
+// Days left out for want of inputs leave their capacities unused
+#![allow(dead_code)]
+
+include!(concat!(env!("OUT_DIR"), "/config.rs"));
@@ -4,69 +4,92 @@ use arrayvec::ArrayVec;
 use nom::{combinator::map_res, IResult};
 use scapegoat::SgMap;
 
-use super::utils::parse::{integer, newline, whitespace};
+use super::utils::parse::{integer, whitespace, LineReader, Source};
+use super::utils::status::{self, Phase};
+use super::utils::{Answers, Bounded, DayResult};
 
 type ResultType = u64;
 
-const FULL: &[u8] = include_bytes!("../../input/day1.full");
-const SAMPLE: &[u8] = include_bytes!("../../input/day1.sample");
+const MAX_LINE: usize = super::config::day1::LINE;
+const MAX_LIST: usize = super::config::day1::LIST;
 
 pub struct Solution {}
 impl super::utils::Solution for Solution {
-    fn new() -> impl super::utils::Solution {
-        Self {}
+    fn new(
+        _scratch: &super::utils::Scratch,
+    ) -> Result<impl super::utils::Solution, super::utils::CapacityExceeded> {
+        Ok(Self {})
     }
 
-    fn run_sample(&mut self) {
-        run("sample", SAMPLE)
+    fn run_sample(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("sample", input, answers)
     }
 
-    fn run_full(&mut self) {
-        run("full", FULL)
+    fn run_full(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("full", input, answers)
+    }
+
+    fn run_stream(
+        &mut self,
+        label: &'static str,
+        source: &mut dyn Source,
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run(label, source, answers)
     }
 }
 
-fn run(label: &'static str, data: &[u8]) {
+fn run(label: &'static str, source: impl Source, answers: &mut Answers) -> DayResult {
     info!("{} start parsing", label);
-    let mut left: ArrayVec<_, 1024> = ArrayVec::new();
-    let mut right: ArrayVec<_, 1024> = ArrayVec::new();
+    status::set(Phase::Parsing);
+    let mut left: ArrayVec<_, MAX_LIST> = ArrayVec::new();
+    let mut right: ArrayVec<_, MAX_LIST> = ArrayVec::new();
 
-    let mut it = nom::combinator::iterator(data, parse_line);
-    for (a, b) in &mut it {
+    let mut lines = LineReader::<_, MAX_LINE>::new(source);
+    while let Some(line) = lines.parse_line(parse_line) {
+        let (a, b) = line?;
         debug!("{} a:{} b:{}", label, a, b);
-        left.push(a);
-        right.push(b);
-    }
-    if it.finish().is_err() {
-        info!("{} error", label);
-    } else {
-        info!("{} processing", label);
-        left.as_mut().sort_unstable();
-        right.as_mut().sort_unstable();
-
-        let answer: ResultType = left
-            .iter()
-            .zip(right.iter())
-            .map(|(a, b)| distance(a, b))
-            .sum();
-        info!("{} part1 answer: {}", label, answer);
-
-        let right_count = right.iter().fold(
-            SgMap::new(),
-            |mut acc: SgMap<ResultType, ResultType, 1024>, value| {
-                let entry: &mut ResultType = acc.entry(*value).or_default();
-                *entry += 1;
-                acc
-            },
-        );
-
-        let answer: ResultType = left
-            .iter()
-            .map(|v| v * right_count.get(v).cloned().unwrap_or_default())
-            .sum();
-
-        info!("{} part2 answer: {}", label, answer);
+        left.push_within(a, "day1 left list")?;
+        right.push_within(b, "day1 right list")?;
     }
+    info!("{} processing", label);
+    status::set(Phase::Solving);
+    left.as_mut().sort_unstable();
+    right.as_mut().sort_unstable();
+
+    let answer: ResultType = left
+        .iter()
+        .zip(right.iter())
+        .map(|(a, b)| distance(a, b))
+        .sum();
+    info!("{} part1 answer: {}", label, answer);
+    answers.record(1, answer);
+
+    let right_count = right.iter().fold(
+        SgMap::new(),
+        |mut acc: SgMap<ResultType, ResultType, MAX_LIST>, value| {
+            let entry: &mut ResultType = acc.entry(*value).or_default();
+            *entry += 1;
+            acc
+        },
+    );
+
+    let answer: ResultType = left
+        .iter()
+        .map(|v| v * right_count.get(v).cloned().unwrap_or_default())
+        .sum();
+
+    info!("{} part2 answer: {}", label, answer);
+    answers.record(2, answer);
+    Ok(())
 }
 
 fn distance(a: &ResultType, b: &ResultType) -> ResultType {
@@ -79,12 +102,7 @@ fn distance(a: &ResultType, b: &ResultType) -> ResultType {
 
 fn parse_line(input: &[u8]) -> IResult<&[u8], (ResultType, ResultType)> {
     map_res(
-        nom::sequence::tuple((
-            integer::<ResultType>,
-            whitespace,
-            integer::<ResultType>,
-            newline,
-        )),
-        |(lhs, _, rhs, _)| Ok::<_, &[u8]>((lhs, rhs)),
+        nom::sequence::tuple((integer::<ResultType>, whitespace, integer::<ResultType>)),
+        |(lhs, _, rhs)| Ok::<_, &[u8]>((lhs, rhs)),
     )(input)
 }
@@ -1,73 +1,104 @@
-use crate::{error, info};
+use crate::info;
 use arrayvec::ArrayVec;
 
 use nom::combinator::map_res;
-use nom::combinator::{iterator, opt};
+use nom::combinator::opt;
 use nom::IResult;
-use scapegoat::SgSet;
 
+use super::utils::parse::items;
 use super::utils::parse::newline;
 use super::utils::parse::non_newline;
+use super::utils::status::{self, Phase};
+use super::utils::{Answers, Bounded, CapacityExceeded, DayResult};
 
 type ResultType = u64;
 
-const FULL: &[u8] = include_bytes!("../../input/day10.full");
-const SAMPLE: &[u8] = include_bytes!("../../input/day10.sample");
+const MAX_GRID: usize = super::config::day10::GRID;
+const NUM_CELLS: usize = MAX_GRID * MAX_GRID;
+/// Summits told apart in one sweep of the grid, a bit each in every cell.
+const SWEEP_SUMMITS: usize = super::config::day10::SUMMITS;
+const WORDS: usize = SWEEP_SUMMITS.div_ceil(32);
 
-pub struct Solution {}
-impl super::utils::Solution for Solution {
-    fn new() -> impl super::utils::Solution {
-        Self {}
+/// Every trail climbs one step at a time, so the cells are worked through
+/// from the summits down, a height at a time, each taking the summits and
+/// trails of its neighbours one higher. No trail is walked more than once,
+/// however many trailheads share it.
+pub struct Solution<'s> {
+    /// The summits reachable from each cell, a bit each
+    reach: &'s mut [u32],
+    /// The trails from each cell to a summit; at most 4 * 3^8 of them
+    ratings: &'s mut [u16],
+}
+impl super::utils::Solution for Solution<'_> {
+    fn new(
+        scratch: &super::utils::Scratch,
+    ) -> Result<impl super::utils::Solution, super::utils::CapacityExceeded> {
+        let reach = scratch.slice_within(NUM_CELLS * WORDS, || 0, "day10 scratch")?;
+        let ratings = scratch.slice_within(NUM_CELLS, || 0, "day10 scratch")?;
+        Ok(Solution { reach, ratings })
     }
 
-    fn run_sample(&mut self) {
-        run("sample", SAMPLE)
+    fn run_sample(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("sample", input, self, answers)
     }
 
-    fn run_full(&mut self) {
-        run("full", FULL)
+    fn run_full(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("full", input, self, answers)
     }
 }
 
-fn run(label: &'static str, data: &[u8]) {
+fn run(
+    label: &'static str,
+    data: &[u8],
+    solution: &mut Solution,
+    answers: &mut Answers,
+) -> DayResult {
     info!("{} start parsing", label);
-    let mut grid = ArrayVec::<&[u8], 64>::new();
-    let mut it = iterator(data, grid_line);
+    status::set(Phase::Parsing);
+    let mut grid = ArrayVec::<&[u8], MAX_GRID>::new();
+    let mut it = items(data, grid_line);
     for line in &mut it {
-        grid.push(line);
+        if line.len() > MAX_GRID {
+            return Err(CapacityExceeded::new("day10 grid columns", MAX_GRID).into());
+        }
+        grid.push_within(line, "day10 grid rows")?;
         if line.is_empty() {
             info!("Manual abort");
             break;
         }
     }
-    match it.finish() {
-        Ok((r, _)) => {
-            if !r.is_empty() {
-                panic!("{} residual size: {} of {}", label, r.len(), data.len())
-            }
-        }
-        Err(_e) => error!("{} error", label),
-    }
+    it.finish()?;
     info!("{} read {} rows", label, grid.len());
     info!("{} start processing", label);
+    status::set(Phase::Solving);
+    let summits = grid
+        .iter()
+        .flat_map(|r| r.iter())
+        .filter(|c| **c == b'9')
+        .count();
     let mut step1_answer = 0;
     let mut step2_answer = 0;
-    let max_y = (grid.len() - 1) as isize;
-    let max_x = (grid.first().unwrap().len() - 1) as isize;
-
-    for (y, r) in grid.iter().enumerate() {
-        for (x, c) in r.iter().enumerate() {
-            if *c == b'0' {
-                let (reached, count) =
-                    get_reachable::<64, 64>(&grid, x as isize, y as isize, max_x, max_y, *c);
-                step1_answer += reached.len() as ResultType;
-                step2_answer += count;
-            }
-        }
+    // Trails are counted in full every sweep; only the summits are shared out
+    for first in (0..summits.max(1)).step_by(WORDS * 32) {
+        status::checkpoint();
+        let (scores, ratings) = sweep(&grid, first, solution);
+        step1_answer += scores;
+        step2_answer = ratings;
     }
 
     info!("{} part1 answer: {}", label, step1_answer);
+    answers.record(1, step1_answer);
     info!("{} part2 answer: {}", label, step2_answer);
+    answers.record(2, step2_answer);
+    Ok(())
 }
 
 fn grid_line(input: &[u8]) -> IResult<&[u8], &[u8]> {
@@ -77,48 +108,62 @@ fn grid_line(input: &[u8]) -> IResult<&[u8], &[u8]> {
     )(input)
 }
 
-fn get_reachable<const C: usize, const N: usize>(
-    grid: &ArrayVec<&[u8], C>,
-    x: isize,
-    y: isize,
-    max_x: isize,
-    max_y: isize,
-    c: u8,
-) -> (SgSet<(isize, isize), N>, usize) {
-    let mut reachable = SgSet::new();
-    let mut total = 0;
-    if c == b'9' {
-        reachable.insert((x, y));
-        return (reachable, 1);
-    }
-    for (dx, dy) in [(0, -1), (0, 1), (1, 0), (-1, 0)] {
-        let nx = x + dx;
-        let ny = y + dy;
-        match get(grid, nx, ny, max_x, max_y) {
-            Some(nc) if (*nc - b'0') == (c - b'0') + 1 => {
-                let (a, b) = get_reachable::<C, N>(grid, nx, ny, max_x, max_y, *nc);
-                for r in a {
-                    reachable.insert(r);
+/// Works out the summits from `first` on that each cell reaches, and the
+/// trails from each, returning the trailheads' total score over those
+/// summits and their total rating.
+fn sweep(grid: &[&[u8]], first: usize, solution: &mut Solution) -> (ResultType, ResultType) {
+    let mut scores = 0;
+    let mut ratings = 0;
+    let mut summit = 0_usize;
+    for height in (b'0'..=b'9').rev() {
+        for (y, row) in grid.iter().enumerate() {
+            for (x, c) in row.iter().enumerate() {
+                if *c != height {
+                    continue;
+                }
+                let mut reach = [0_u32; WORDS];
+                let mut rating = 0;
+                if height == b'9' {
+                    if let Some(bit) = summit.checked_sub(first).filter(|b| *b < WORDS * 32) {
+                        reach[bit / 32] |= 1 << (bit % 32);
+                    }
+                    summit += 1;
+                    rating = 1;
+                } else {
+                    for cell in neighbours(grid, x, y, height + 1) {
+                        let above = &solution.reach[cell * WORDS..][..WORDS];
+                        for (word, above) in reach.iter_mut().zip(above) {
+                            *word |= above;
+                        }
+                        rating += solution.ratings[cell];
+                    }
+                }
+                let cell = y * MAX_GRID + x;
+                solution.reach[cell * WORDS..][..WORDS].copy_from_slice(&reach);
+                solution.ratings[cell] = rating;
+                if height == b'0' {
+                    scores += reach.iter().map(|w| w.count_ones()).sum::<u32>() as ResultType;
+                    ratings += rating as ResultType;
                 }
-                total += b;
             }
-            _ => {}
         }
     }
-    (reachable, total)
+    (scores, ratings)
 }
 
-fn get<'a, const C: usize>(
-    grid: &'a ArrayVec<&[u8], C>,
-    x: isize,
-    y: isize,
-    max_x: isize,
-    max_y: isize,
-) -> Option<&'a u8> {
-    if x < 0 || x > max_x || y < 0 || y > max_y {
-        None
-    } else {
-        grid.get(y as usize).and_then(|r| r.get(x as usize))
-        //.inspect(|v| info!("valid read from ({},{}) => {}", x, y, v))
-    }
+/// The cells next to `(x, y)` at `height`.
+fn neighbours<'g>(
+    grid: &'g [&[u8]],
+    x: usize,
+    y: usize,
+    height: u8,
+) -> impl Iterator<Item = usize> + 'g {
+    [(0, -1), (0, 1), (1, 0), (-1, 0)]
+        .into_iter()
+        .filter_map(move |(dx, dy)| {
+            let nx = x.checked_add_signed(dx)?;
+            let ny = y.checked_add_signed(dy)?;
+            let c = grid.get(ny).and_then(|r| r.get(nx))?;
+            (*c == height).then_some(ny * MAX_GRID + nx)
+        })
 }
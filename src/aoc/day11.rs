@@ -0,0 +1,185 @@
+use crate::{debug, info};
+
+use nom::bytes::complete::tag;
+use nom::combinator::map_res;
+use nom::combinator::opt;
+use nom::IResult;
+
+use super::utils::parse::{integer, items, newline};
+use super::utils::status::{self, Phase};
+use super::utils::{Answers, CapacityExceeded, DayResult};
+
+type ResultType = u64;
+
+/// Number of distinct stone values the counting table can hold.
+/// Real inputs settle at a little under 4000 distinct values after 75 blinks.
+const NUM_STONES: usize = super::config::day11::STONES;
+
+pub struct Solution<'s> {
+    stones: Stones<'s>,
+}
+impl super::utils::Solution for Solution<'_> {
+    fn new(
+        scratch: &super::utils::Scratch,
+    ) -> Result<impl super::utils::Solution, super::utils::CapacityExceeded> {
+        let slots = scratch.slice_within(NUM_STONES, || Slot::EMPTY, "day11 scratch")?;
+        Ok(Solution {
+            stones: Stones { slots, current: 0 },
+        })
+    }
+
+    fn run_sample(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("sample", input, &mut self.stones, answers)
+    }
+
+    fn run_full(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("full", input, &mut self.stones, answers)
+    }
+}
+
+fn run(label: &'static str, data: &[u8], stones: &mut Stones, answers: &mut Answers) -> DayResult {
+    info!("{} start parsing", label);
+    status::set(Phase::Parsing);
+    stones.clear();
+    let mut it = items(data, stone);
+    for value in &mut it {
+        stones.add(value, 1)?;
+    }
+    it.finish()?;
+    info!("{} start processing", label);
+    status::set(Phase::Solving);
+    for blink in 0..75 {
+        status::checkpoint();
+        stones.blink()?;
+        debug!("{} blink {}: {} distinct", label, blink + 1, stones.len());
+        if blink + 1 == 25 {
+            info!("{} part1 answer: {}", label, stones.total());
+            answers.record(1, stones.total());
+        }
+    }
+    info!("{} part2 answer: {}", label, stones.total());
+    answers.record(2, stones.total());
+    Ok(())
+}
+
+fn stone(input: &[u8]) -> IResult<&[u8], ResultType> {
+    map_res(
+        nom::sequence::tuple((integer, opt(tag(b" ")), opt(newline))),
+        |(value, _, _)| Ok::<_, &[u8]>(value),
+    )(input)
+}
+
+/// The order of stones never matters, only how many of each engraved value
+/// there are, so each blink maps one generation of counts onto the next.
+///
+/// Values are kept in an open-addressed table; both generations live
+/// side-by-side in each slot so that the table never needs to be rebuilt.
+struct Stones<'s> {
+    slots: &'s mut [Slot],
+    current: usize,
+}
+
+#[derive(Copy, Clone)]
+struct Slot {
+    value: ResultType,
+    count: [ResultType; 2],
+}
+
+impl Slot {
+    const EMPTY: Slot = Slot {
+        value: ResultType::MAX,
+        count: [0; 2],
+    };
+
+    fn is_empty(&self) -> bool {
+        self.value == ResultType::MAX
+    }
+}
+
+impl Stones<'_> {
+    fn clear(&mut self) {
+        self.slots.fill(Slot::EMPTY);
+        self.current = 0;
+    }
+
+    fn len(&self) -> usize {
+        self.slots
+            .iter()
+            .filter(|slot| slot.count[self.current] != 0)
+            .count()
+    }
+
+    fn total(&self) -> ResultType {
+        self.slots.iter().map(|slot| slot.count[self.current]).sum()
+    }
+
+    fn add(&mut self, value: ResultType, count: ResultType) -> DayResult {
+        self.add_to(self.current, value, count)
+    }
+
+    fn add_to(&mut self, generation: usize, value: ResultType, count: ResultType) -> DayResult {
+        let start = (value.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 32) as usize % NUM_STONES;
+        for probe in 0..NUM_STONES {
+            let slot = &mut self.slots[(start + probe) % NUM_STONES];
+            if slot.is_empty() {
+                slot.value = value;
+            }
+            if slot.value == value {
+                slot.count[generation] += count;
+                return Ok(());
+            }
+        }
+        Err(CapacityExceeded::new("day11 distinct stones", NUM_STONES).into())
+    }
+
+    fn blink(&mut self) -> DayResult {
+        let current = self.current;
+        let next = 1 - current;
+        for slot in self.slots.iter_mut() {
+            slot.count[next] = 0;
+        }
+        for idx in 0..NUM_STONES {
+            let Slot { value, count } = self.slots[idx];
+            let count = count[current];
+            if count == 0 {
+                continue;
+            }
+            let (lhs, rhs) = change(value);
+            self.add_to(next, lhs, count)?;
+            if let Some(rhs) = rhs {
+                self.add_to(next, rhs, count)?;
+            }
+        }
+        self.current = next;
+        Ok(())
+    }
+}
+
+fn change(value: ResultType) -> (ResultType, Option<ResultType>) {
+    if value == 0 {
+        return (1, None);
+    }
+    let mut digits = 0;
+    let mut scale = 1;
+    while scale <= value {
+        scale *= 10;
+        digits += 1;
+    }
+    if digits % 2 == 0 {
+        let mut half = 1;
+        for _ in 0..digits / 2 {
+            half *= 10;
+        }
+        (value / half, Some(value % half))
+    } else {
+        (value * 2024, None)
+    }
+}
@@ -0,0 +1,165 @@
+use crate::{debug, info};
+use arrayvec::ArrayVec;
+
+use nom::combinator::map_res;
+use nom::combinator::opt;
+use nom::IResult;
+
+use super::utils::parse::items;
+use super::utils::parse::newline;
+use super::utils::parse::non_newline;
+use super::utils::status::{self, Phase};
+use super::utils::{Answers, Bounded, CapacityExceeded, DayResult};
+
+type ResultType = u64;
+
+const MAX_SIZE: usize = super::config::day12::GRID;
+const NUM_CELLS: usize = MAX_SIZE * MAX_SIZE;
+const _: () = assert!(NUM_CELLS <= 1 << 16, "cells are stacked as u16");
+
+pub struct Solution<'s> {
+    pending: &'s mut [u16],
+}
+impl super::utils::Solution for Solution<'_> {
+    fn new(
+        scratch: &super::utils::Scratch,
+    ) -> Result<impl super::utils::Solution, super::utils::CapacityExceeded> {
+        let pending = scratch.slice_within(NUM_CELLS, || 0, "day12 scratch")?;
+        Ok(Solution { pending })
+    }
+
+    fn run_sample(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("sample", input, self.pending, answers)
+    }
+
+    fn run_full(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("full", input, self.pending, answers)
+    }
+}
+
+fn run(label: &'static str, data: &[u8], pending: &mut [u16], answers: &mut Answers) -> DayResult {
+    info!("{} start parsing", label);
+    status::set(Phase::Parsing);
+    let mut grid = ArrayVec::<&[u8], MAX_SIZE>::new();
+    let mut it = items(data, grid_line);
+    for line in &mut it {
+        if line.len() > MAX_SIZE {
+            return Err(CapacityExceeded::new("day12 grid columns", MAX_SIZE).into());
+        }
+        grid.push_within(line, "day12 grid rows")?;
+    }
+    it.finish()?;
+    info!("{} read {} rows", label, grid.len());
+    info!("{} start processing", label);
+    status::set(Phase::Solving);
+
+    let mut visited = [0_u32; NUM_CELLS / 32 + 1];
+    let mut part1_answer: ResultType = 0;
+    let mut part2_answer: ResultType = 0;
+    for (y, row) in grid.iter().enumerate() {
+        for x in 0..row.len() {
+            let idx = x + y * MAX_SIZE;
+            if visited[idx / 32] & (1 << (idx % 32)) != 0 {
+                continue;
+            }
+            let region = measure(&grid, x, y, &mut visited, pending);
+            debug!(
+                "{} region {} @ ({},{}): area {}, perimeter {}, sides {}",
+                label, row[x] as char, x, y, region.area, region.perimeter, region.sides
+            );
+            part1_answer += region.area * region.perimeter;
+            part2_answer += region.area * region.sides;
+        }
+    }
+    info!("{} part1 answer: {}", label, part1_answer);
+    answers.record(1, part1_answer);
+    info!("{} part2 answer: {}", label, part2_answer);
+    answers.record(2, part2_answer);
+    Ok(())
+}
+
+fn grid_line(input: &[u8]) -> IResult<&[u8], &[u8]> {
+    map_res(
+        nom::sequence::tuple((non_newline, opt(newline))),
+        |(line, _)| Ok::<_, &[u8]>(line),
+    )(input)
+}
+
+#[derive(Default)]
+struct Region {
+    area: ResultType,
+    perimeter: ResultType,
+    sides: ResultType,
+}
+
+/// Flood-fills the region containing (`sx`, `sy`).
+///
+/// A region has exactly as many sides as it has corners, and every corner can
+/// be spotted from a single cell by looking at its two orthogonal neighbours
+/// and the diagonal between them, so no per-edge bookkeeping is required.
+///
+/// `pending` is used as a stack of cells still to explore; each cell is marked
+/// visited as it is pushed so it never needs more than one entry per cell.
+fn measure<const C: usize>(
+    grid: &ArrayVec<&[u8], C>,
+    sx: usize,
+    sy: usize,
+    visited: &mut [u32],
+    pending: &mut [u16],
+) -> Region {
+    let plant = grid[sy][sx];
+    let same = |x: isize, y: isize| get(grid, x, y) == Some(plant);
+
+    let mut region = Region::default();
+    let start = sx + sy * MAX_SIZE;
+    visited[start / 32] |= 1 << (start % 32);
+    pending[0] = start as u16;
+    let mut top = 1;
+    while top > 0 {
+        top -= 1;
+        let idx = pending[top];
+        let x = (idx as usize % MAX_SIZE) as isize;
+        let y = (idx as usize / MAX_SIZE) as isize;
+        region.area += 1;
+        for (dx, dy) in [(0, -1), (1, 0), (0, 1), (-1, 0)] {
+            let (nx, ny) = (x + dx, y + dy);
+            if !same(nx, ny) {
+                region.perimeter += 1;
+                continue;
+            }
+            let nidx = nx as usize + ny as usize * MAX_SIZE;
+            if visited[nidx / 32] & (1 << (nidx % 32)) == 0 {
+                visited[nidx / 32] |= 1 << (nidx % 32);
+                pending[top] = nidx as u16;
+                top += 1;
+            }
+        }
+        for (dx, dy) in [(1, -1), (1, 1), (-1, 1), (-1, -1)] {
+            let horizontal = same(x + dx, y);
+            let vertical = same(x, y + dy);
+            let diagonal = same(x + dx, y + dy);
+            if (!horizontal && !vertical) || (horizontal && vertical && !diagonal) {
+                region.sides += 1;
+            }
+        }
+    }
+    region
+}
+
+fn get<const C: usize>(grid: &ArrayVec<&[u8], C>, x: isize, y: isize) -> Option<u8> {
+    if x < 0 || y < 0 {
+        None
+    } else {
+        grid.get(y as usize)
+            .and_then(|r| r.get(x as usize))
+            .cloned()
+    }
+}
@@ -0,0 +1,124 @@
+use crate::{debug, info};
+
+use nom::bytes::complete::tag;
+use nom::combinator::map_res;
+use nom::combinator::opt;
+use nom::sequence::tuple;
+use nom::IResult;
+
+use super::utils::parse::{coordinate, items, newline};
+use super::utils::status::{self, Phase};
+use super::utils::{Answers, DayResult};
+
+type ResultType = i64;
+
+const PART2_OFFSET: ResultType = 10_000_000_000_000;
+
+pub struct Solution {}
+impl super::utils::Solution for Solution {
+    fn new(
+        _scratch: &super::utils::Scratch,
+    ) -> Result<impl super::utils::Solution, super::utils::CapacityExceeded> {
+        Ok(Self {})
+    }
+
+    fn run_sample(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("sample", input, answers)
+    }
+
+    fn run_full(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("full", input, answers)
+    }
+}
+
+fn run(label: &'static str, data: &[u8], answers: &mut Answers) -> DayResult {
+    info!("{} start parsing", label);
+    status::set(Phase::Parsing);
+    let mut part1_answer = 0;
+    let mut part2_answer = 0;
+    let mut it = items(data, machine);
+    for (row, machine) in (&mut it).enumerate() {
+        let part1 = machine.cost(0);
+        let part2 = machine.cost(PART2_OFFSET);
+        debug!("{} {}: {:?} {:?}", label, row, part1, part2);
+        part1_answer += part1.unwrap_or_default();
+        part2_answer += part2.unwrap_or_default();
+    }
+    it.finish()?;
+    info!("{} part1 answer: {}", label, part1_answer);
+    answers.record(1, part1_answer);
+    info!("{} part2 answer: {}", label, part2_answer);
+    answers.record(2, part2_answer);
+    Ok(())
+}
+
+struct Machine {
+    a: (ResultType, ResultType),
+    b: (ResultType, ResultType),
+    prize: (ResultType, ResultType),
+}
+
+impl Machine {
+    /// Solves `a * A + b * B = prize` with Cramer's rule.
+    ///
+    /// The prize is up to 10^13 away once the part 2 offset is applied, so the
+    /// cross products are checked rather than trusted to fit in an `i64`.
+    fn cost(&self, offset: ResultType) -> Option<ResultType> {
+        let (ax, ay) = self.a;
+        let (bx, by) = self.b;
+        let px = self.prize.0.checked_add(offset)?;
+        let py = self.prize.1.checked_add(offset)?;
+
+        let det = ax.checked_mul(by)?.checked_sub(ay.checked_mul(bx)?)?;
+        if det == 0 {
+            return None;
+        }
+        let a = px.checked_mul(by)?.checked_sub(py.checked_mul(bx)?)?;
+        let b = ax.checked_mul(py)?.checked_sub(ay.checked_mul(px)?)?;
+        if a % det != 0 || b % det != 0 {
+            return None;
+        }
+        let (a, b) = (a / det, b / det);
+        if a < 0 || b < 0 || (offset == 0 && (a > 100 || b > 100)) {
+            return None;
+        }
+        a.checked_mul(3)?.checked_add(b)
+    }
+}
+
+fn button<'a>(
+    name: &'static [u8],
+) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], (ResultType, ResultType)> {
+    map_res(
+        tuple((
+            tag(b"Button "),
+            tag(name),
+            tag(b": "),
+            coordinate(b"X+", b"Y+"),
+            newline,
+        )),
+        |(_, _, _, xy, _)| Ok::<_, &[u8]>(xy),
+    )
+}
+
+fn prize(input: &[u8]) -> IResult<&[u8], (ResultType, ResultType)> {
+    map_res(
+        tuple((tag(b"Prize: "), coordinate(b"X=", b"Y="), opt(newline))),
+        |(_, xy, _)| Ok::<_, &[u8]>(xy),
+    )(input)
+}
+
+fn machine(input: &[u8]) -> IResult<&[u8], Machine> {
+    map_res(
+        tuple((button(b"A"), button(b"B"), prize)),
+        |(a, b, prize)| Ok::<_, &[u8]>(Machine { a, b, prize }),
+    )(input)
+}
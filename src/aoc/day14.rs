@@ -0,0 +1,154 @@
+use crate::{debug, info};
+use arrayvec::ArrayVec;
+
+use nom::combinator::map_res;
+use nom::combinator::opt;
+use nom::sequence::tuple;
+use nom::IResult;
+
+use super::utils::parse::{coordinate, items, newline, whitespace};
+use super::utils::status::{self, Phase};
+use super::utils::{Answers, Bounded, DayError, DayResult};
+
+type ResultType = u64;
+
+const MAX_ROBOTS: usize = super::config::day14::ROBOTS;
+const MAX_WIDTH: usize = 101;
+const MAX_HEIGHT: usize = 103;
+
+/// The picture is framed, so a long unbroken horizontal line of robots only
+/// shows up once they form it.
+const EASTER_EGG_RUN: usize = 10;
+
+pub struct Solution {}
+impl super::utils::Solution for Solution {
+    fn new(
+        _scratch: &super::utils::Scratch,
+    ) -> Result<impl super::utils::Solution, super::utils::CapacityExceeded> {
+        Ok(Self {})
+    }
+
+    fn run_sample(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("sample", input, 11, 7, false, answers)
+    }
+
+    fn run_full(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run(
+            "full",
+            input,
+            MAX_WIDTH as i32,
+            MAX_HEIGHT as i32,
+            true,
+            answers,
+        )
+    }
+}
+
+fn run(
+    label: &'static str,
+    data: &[u8],
+    width: i32,
+    height: i32,
+    has_tree: bool,
+    answers: &mut Answers,
+) -> DayResult {
+    info!("{} start parsing", label);
+    status::set(Phase::Parsing);
+    let mut robots = ArrayVec::<Robot, MAX_ROBOTS>::new();
+    let mut it = items(data, robot);
+    for robot in &mut it {
+        robots.push_within(robot, "day14 robots")?;
+    }
+    it.finish()?;
+    info!("{} read {} robots", label, robots.len());
+    info!("{} start processing", label);
+    status::set(Phase::Solving);
+
+    let mut quadrants = [0 as ResultType; 4];
+    for robot in robots.iter() {
+        let (x, y) = robot.position_at(100, width, height);
+        let (mid_x, mid_y) = (width / 2, height / 2);
+        if x == mid_x || y == mid_y {
+            continue;
+        }
+        quadrants[(x > mid_x) as usize + 2 * (y > mid_y) as usize] += 1;
+    }
+    let part1_answer: ResultType = quadrants.iter().product();
+    info!("{} part1 answer: {}", label, part1_answer);
+    answers.record(1, part1_answer);
+
+    // The sample has too few robots to draw anything
+    if !has_tree {
+        return Ok(());
+    }
+    // Positions repeat with a period of width * height
+    let part2_answer = (1..=width * height)
+        .find(|t| {
+            status::checkpoint();
+            has_easter_egg(robots.as_ref(), *t, width, height)
+        })
+        .ok_or(DayError::Invalid("robots never draw the tree"))?;
+    info!("{} part2 answer: {}", label, part2_answer);
+    answers.record(2, part2_answer);
+    Ok(())
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Robot {
+    position: (i32, i32),
+    velocity: (i32, i32),
+}
+
+impl Robot {
+    fn position_at(&self, t: i32, width: i32, height: i32) -> (i32, i32) {
+        (
+            (self.position.0 + self.velocity.0 * t).rem_euclid(width),
+            (self.position.1 + self.velocity.1 * t).rem_euclid(height),
+        )
+    }
+}
+
+fn has_easter_egg(robots: &[Robot], t: i32, width: i32, height: i32) -> bool {
+    let mut occupied = [0_u32; (MAX_WIDTH * MAX_HEIGHT) / 32 + 1];
+    for robot in robots {
+        let (x, y) = robot.position_at(t, width, height);
+        let idx = (x + y * width) as usize;
+        occupied[idx / 32] |= 1 << (idx % 32);
+    }
+    for y in 0..height {
+        let mut run = 0;
+        for x in 0..width {
+            let idx = (x + y * width) as usize;
+            if occupied[idx / 32] & (1 << (idx % 32)) == 0 {
+                run = 0;
+                continue;
+            }
+            run += 1;
+            if run >= EASTER_EGG_RUN {
+                debug!("easter egg at t={} row {}", t, y);
+                return true;
+            }
+        }
+    }
+    false
+}
+
+fn robot(input: &[u8]) -> IResult<&[u8], Robot> {
+    map_res(
+        tuple((
+            coordinate(b"p=", b""),
+            whitespace,
+            coordinate(b"v=", b""),
+            opt(newline),
+        )),
+        |(position, _, velocity, _)| Ok::<_, &[u8]>(Robot { position, velocity }),
+    )(input)
+}
@@ -0,0 +1,238 @@
+use crate::{debug, info};
+use arrayvec::ArrayVec;
+
+use super::utils::direction::Direction;
+use super::utils::status::{self, Phase};
+use super::utils::{Answers, Bounded, CapacityExceeded, DayError, DayResult};
+
+type ResultType = u64;
+
+const MAX_SIZE: usize = super::config::day15::GRID;
+/// Part 2 doubles the width of everything except the robot.
+const NUM_CELLS: usize = 2 * MAX_SIZE * MAX_SIZE;
+/// Upper bound on the boxes shifted by a single vertical push in part 2.
+const MAX_PUSHED: usize = super::config::day15::PUSHED;
+
+pub struct Solution {}
+impl super::utils::Solution for Solution {
+    fn new(
+        _scratch: &super::utils::Scratch,
+    ) -> Result<impl super::utils::Solution, super::utils::CapacityExceeded> {
+        Ok(Self {})
+    }
+
+    fn run_sample(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("sample", input, answers)
+    }
+
+    fn run_full(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("full", input, answers)
+    }
+}
+
+fn run(label: &'static str, data: &[u8], answers: &mut Answers) -> DayResult {
+    info!("{} start parsing", label);
+    status::set(Phase::Parsing);
+    let mut lines = data
+        .split(|c| *c == b'\n')
+        .map(|line| line.strip_suffix(b"\r").unwrap_or(line));
+    let mut map = ArrayVec::<&[u8], MAX_SIZE>::new();
+    for line in &mut lines {
+        if line.is_empty() {
+            break;
+        }
+        if line.len() > MAX_SIZE {
+            return Err(CapacityExceeded::new("day15 warehouse columns", MAX_SIZE).into());
+        }
+        map.push_within(line, "day15 warehouse rows")?;
+    }
+    info!("{} read {} rows", label, map.len());
+    info!("{} start processing", label);
+    status::set(Phase::Solving);
+
+    let mut warehouse = Warehouse::new(&map, false)?;
+    for step in lines.clone().flatten() {
+        if let Some(direction) = direction(*step) {
+            warehouse.step(direction)?;
+        }
+    }
+    info!("{} part1 answer: {}", label, warehouse.gps());
+    answers.record(1, warehouse.gps());
+
+    let mut warehouse = Warehouse::new(&map, true)?;
+    for step in lines.flatten() {
+        if let Some(direction) = direction(*step) {
+            warehouse.step(direction)?;
+        }
+    }
+    info!("{} part2 answer: {}", label, warehouse.gps());
+    answers.record(2, warehouse.gps());
+    Ok(())
+}
+
+fn direction(c: u8) -> Option<Direction> {
+    match c {
+        b'^' => Some(Direction::N),
+        b'>' => Some(Direction::E),
+        b'v' => Some(Direction::S),
+        b'<' => Some(Direction::W),
+        _ => None,
+    }
+}
+
+struct Warehouse {
+    cells: [u8; NUM_CELLS],
+    width: usize,
+    robot: (usize, usize),
+}
+
+impl Warehouse {
+    /// Fails on anything other than walls, floor, boxes and the robot, and
+    /// on rows of differing lengths.
+    fn new<const C: usize>(map: &ArrayVec<&[u8], C>, wide: bool) -> Result<Self, DayError> {
+        let scale = if wide { 2 } else { 1 };
+        let width = map.first().map(|row| row.len()).unwrap_or_default() * scale;
+        let mut warehouse = Self {
+            cells: [b'#'; NUM_CELLS],
+            width,
+            robot: (0, 0),
+        };
+        for (y, row) in map.iter().enumerate() {
+            if row.len() * scale != width {
+                return Err(DayError::Invalid("warehouse rows differ in length"));
+            }
+            for (x, c) in row.iter().enumerate() {
+                let expanded: &[u8] = match (c, wide) {
+                    (b'@' | b'O' | b'#' | b'.', false) => core::slice::from_ref(c),
+                    (b'@', true) => b"@.",
+                    (b'O', true) => b"[]",
+                    (b'#', true) => b"##",
+                    (b'.', true) => b"..",
+                    _ => return Err(DayError::Invalid("unknown warehouse cell")),
+                };
+                for (i, e) in expanded.iter().enumerate() {
+                    let (ex, ey) = (x * scale + i, y);
+                    if *e == b'@' {
+                        warehouse.robot = (ex, ey);
+                        warehouse.cells[ex + ey * width] = b'.';
+                    } else {
+                        warehouse.cells[ex + ey * width] = *e;
+                    }
+                }
+            }
+        }
+        Ok(warehouse)
+    }
+
+    fn get(&self, x: usize, y: usize) -> u8 {
+        self.cells.get(x + y * self.width).cloned().unwrap_or(b'#')
+    }
+
+    fn set(&mut self, x: usize, y: usize, c: u8) {
+        self.cells[x + y * self.width] = c;
+    }
+
+    /// Moves the robot one step, pushing any boxes in the way.
+    /// Fails if more boxes would move than can be tracked, or on a move or
+    /// cell the warehouse has no rule for.
+    fn step(&mut self, direction: Direction) -> DayResult {
+        let (dx, dy) = match direction {
+            Direction::N => (0, -1),
+            Direction::E => (1, 0),
+            Direction::S => (0, 1),
+            Direction::W => (-1, 0),
+            _ => return Err(DayError::Invalid("diagonal robot move")),
+        };
+        let (x, y) = self.robot;
+        let (nx, ny) = (x.wrapping_add_signed(dx), y.wrapping_add_signed(dy));
+        let moved = match self.get(nx, ny) {
+            b'.' => true,
+            b'#' => false,
+            b'O' => self.push_line(nx, ny, dx, dy),
+            b'[' | b']' if dy == 0 => self.push_line(nx, ny, dx, dy),
+            b'[' | b']' => self.push_wide(nx, ny, dy)?,
+            _ => return Err(DayError::Invalid("unknown warehouse cell")),
+        };
+        if moved {
+            debug!("robot -> ({},{})", nx, ny);
+            self.robot = (nx, ny);
+        }
+        Ok(())
+    }
+
+    /// Shifts a straight run of box cells one step along (`dx`, `dy`), if
+    /// there is a gap before the next wall.
+    fn push_line(&mut self, x: usize, y: usize, dx: isize, dy: isize) -> bool {
+        let (mut ex, mut ey) = (x, y);
+        loop {
+            match self.get(ex, ey) {
+                b'.' => break,
+                b'#' => return false,
+                _ => {
+                    ex = ex.wrapping_add_signed(dx);
+                    ey = ey.wrapping_add_signed(dy);
+                }
+            }
+        }
+        // Walk back from the gap, shuffling each cell forward
+        while (ex, ey) != (x, y) {
+            let (px, py) = (ex.wrapping_add_signed(-dx), ey.wrapping_add_signed(-dy));
+            let c = self.get(px, py);
+            self.set(ex, ey, c);
+            (ex, ey) = (px, py);
+        }
+        self.set(x, y, b'.');
+        true
+    }
+
+    /// Pushes wide boxes vertically; one box can rest on two others, so the
+    /// set of affected boxes fans out and is gathered breadth-first first.
+    fn push_wide(&mut self, x: usize, y: usize, dy: isize) -> Result<bool, CapacityExceeded> {
+        let mut boxes = ArrayVec::<(usize, usize), MAX_PUSHED>::new();
+        let left = if self.get(x, y) == b'[' { x } else { x - 1 };
+        boxes.push((left, y));
+        let mut next = 0;
+        while next < boxes.len() {
+            let (bx, by) = boxes[next];
+            next += 1;
+            let ny = by.wrapping_add_signed(dy);
+            for cx in [bx, bx + 1] {
+                let above = match self.get(cx, ny) {
+                    b'#' => return Ok(false),
+                    b'[' => (cx, ny),
+                    b']' => (cx - 1, ny),
+                    _ => continue,
+                };
+                if !boxes.contains(&above) {
+                    boxes.push_within(above, "day15 boxes pushed at once")?;
+                }
+            }
+        }
+        // Boxes were found in order of distance, so move the furthest first
+        for (bx, by) in boxes.iter().rev() {
+            let ny = by.wrapping_add_signed(dy);
+            self.set(*bx, *by, b'.');
+            self.set(*bx + 1, *by, b'.');
+            self.set(*bx, ny, b'[');
+            self.set(*bx + 1, ny, b']');
+        }
+        Ok(true)
+    }
+
+    fn gps(&self) -> ResultType {
+        self.cells
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| **c == b'O' || **c == b'[')
+            .map(|(idx, _)| (100 * (idx / self.width) + idx % self.width) as ResultType)
+            .sum()
+    }
+}
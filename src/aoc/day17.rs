@@ -0,0 +1,182 @@
+use core::fmt::Write;
+
+use crate::{debug, info};
+use arrayvec::{ArrayString, ArrayVec};
+
+use nom::bytes::complete::tag;
+use nom::bytes::complete::take;
+use nom::combinator::map_res;
+use nom::combinator::opt;
+use nom::sequence::tuple;
+use nom::IResult;
+
+use super::utils::parse::{integer, newline, separated, List, ParseError};
+use super::utils::status::{self, Phase};
+use super::utils::{Answers, Bounded, DayError, DayResult};
+
+type ResultType = u64;
+
+const MAX_PROGRAM: usize = super::config::day17::PROGRAM;
+/// Guards against programs that never halt.
+const MAX_STEPS: usize = 10_000;
+
+pub struct Solution {}
+impl super::utils::Solution for Solution {
+    fn new(
+        _scratch: &super::utils::Scratch,
+    ) -> Result<impl super::utils::Solution, super::utils::CapacityExceeded> {
+        Ok(Self {})
+    }
+
+    fn run_sample(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("sample", input, answers)
+    }
+
+    fn run_full(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("full", input, answers)
+    }
+}
+
+fn run(label: &'static str, data: &[u8], answers: &mut Answers) -> DayResult {
+    info!("{} start parsing", label);
+    status::set(Phase::Parsing);
+    let (registers, program) = match parse(data) {
+        Ok((_, parsed)) => parsed,
+        Err(e) => return Err(ParseError::from_nom(data, e).into()),
+    };
+    let program = program?;
+    debug!(
+        "{} registers {:?}, {} instructions",
+        label,
+        registers,
+        program.len()
+    );
+    info!("{} start processing", label);
+    status::set(Phase::Solving);
+
+    let mut part1_answer = ArrayString::<{ 2 * MAX_PROGRAM }>::new();
+    let output = execute(program.as_ref(), registers)?;
+    for (i, v) in output.iter().enumerate() {
+        let separator = if i == 0 { "" } else { "," };
+        let _ = write!(part1_answer, "{}{}", separator, v);
+    }
+    info!("{} part1 answer: {}", label, part1_answer.as_str());
+    answers.record(1, part1_answer.as_str());
+
+    let part2_answer = find_quine(program.as_ref(), registers, program.len(), 0)?
+        .ok_or(DayError::Invalid("no value of A outputs the program"))?;
+    info!("{} part2 answer: {}", label, part2_answer);
+    answers.record(2, part2_answer);
+    Ok(())
+}
+
+/// Every input shifts A right by three bits per loop and prints a value derived
+/// from its low bits, so the program is rebuilt from its last output backwards,
+/// three bits of A at a time.
+fn find_quine(
+    program: &[u8],
+    registers: [ResultType; 3],
+    remaining: usize,
+    a: ResultType,
+) -> Result<Option<ResultType>, DayError> {
+    if remaining == 0 {
+        return Ok(Some(a));
+    }
+    let wanted = &program[remaining - 1..];
+    for bits in 0..8 {
+        let candidate = (a << 3) | bits;
+        if candidate == 0 {
+            continue;
+        }
+        let output = execute(program, [candidate, registers[1], registers[2]])?;
+        if output.as_ref() == wanted {
+            if let Some(found) = find_quine(program, registers, remaining - 1, candidate)? {
+                return Ok(Some(found));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Runs `program` to completion, failing on a reserved operand or opcode,
+/// or if it is still going after `MAX_STEPS` instructions.
+fn execute(
+    program: &[u8],
+    registers: [ResultType; 3],
+) -> Result<ArrayVec<u8, MAX_PROGRAM>, DayError> {
+    let [mut a, mut b, mut c] = registers;
+    let mut output = ArrayVec::new();
+    let mut ip = 0;
+    for _ in 0..MAX_STEPS {
+        let (Some(opcode), Some(operand)) = (program.get(ip), program.get(ip + 1)) else {
+            return Ok(output);
+        };
+        let literal = *operand as ResultType;
+        let combo = match operand {
+            0..=3 => literal,
+            4 => a,
+            5 => b,
+            6 => c,
+            // Only reserved where the instruction reads a combo operand
+            _ if matches!(opcode, 1 | 3 | 4) => 0,
+            _ => return Err(DayError::Invalid("reserved combo operand 7")),
+        };
+        ip += 2;
+        match opcode {
+            0 => a = shift(a, combo),
+            1 => b ^= literal,
+            2 => b = combo % 8,
+            3 if a != 0 => ip = literal as usize,
+            3 => {}
+            4 => b ^= c,
+            5 => output.push_within((combo % 8) as u8, "day17 output")?,
+            6 => b = shift(a, combo),
+            7 => c = shift(a, combo),
+            _ => return Err(DayError::Invalid("unknown opcode")),
+        }
+    }
+    Err(DayError::Invalid("program does not halt"))
+}
+
+/// `value / 2^by`, which is 0 once every bit has been shifted out.
+fn shift(value: ResultType, by: ResultType) -> ResultType {
+    u32::try_from(by)
+        .ok()
+        .and_then(|by| value.checked_shr(by))
+        .unwrap_or_default()
+}
+
+fn register(input: &[u8]) -> IResult<&[u8], ResultType> {
+    map_res(
+        tuple((
+            tag(b"Register "),
+            take(1_usize),
+            tag(b": "),
+            integer,
+            newline,
+        )),
+        |(_, _, _, value, _)| Ok::<_, &[u8]>(value),
+    )(input)
+}
+
+fn parse(input: &[u8]) -> IResult<&[u8], ([ResultType; 3], List<u8, MAX_PROGRAM>)> {
+    map_res(
+        tuple((
+            register,
+            register,
+            register,
+            tag(b"Program: "),
+            separated(b",", "day17 program", integer::<u8>),
+            opt(newline),
+        )),
+        |(a, b, c, _, program, _)| Ok::<_, &[u8]>(([a, b, c], program)),
+    )(input)
+}
@@ -0,0 +1,181 @@
+use crate::{debug, info};
+
+use nom::bytes::complete::tag;
+use nom::combinator::map_res;
+use nom::combinator::opt;
+use nom::sequence::tuple;
+use nom::IResult;
+
+use super::utils::parse::{integer, items, newline};
+use super::utils::status::{self, Phase};
+use super::utils::{Answers, CapacityExceeded, DayError, DayResult};
+
+const MAX_SIZE: usize = super::config::day18::GRID;
+const NUM_CELLS: usize = MAX_SIZE * MAX_SIZE;
+const _: () = assert!(NUM_CELLS <= 1 << 16, "cells are queued as u16");
+/// Width of the full input's memory space, as the puzzle sets it.
+const FULL_SIZE: usize = 71;
+/// Marks a cell that no byte ever falls on.
+const NEVER: u16 = u16::MAX;
+
+pub struct Solution<'s> {
+    memory: Memory<'s>,
+}
+impl super::utils::Solution for Solution<'_> {
+    fn new(
+        scratch: &super::utils::Scratch,
+    ) -> Result<impl super::utils::Solution, super::utils::CapacityExceeded> {
+        let fall_time = scratch.slice_within(NUM_CELLS, || NEVER, "day18 scratch")?;
+        let queue = scratch.slice_within(NUM_CELLS, || 0, "day18 scratch")?;
+        Ok(Solution {
+            memory: Memory { fall_time, queue },
+        })
+    }
+
+    fn run_sample(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("sample", input, 7, 12, &mut self.memory, answers)
+    }
+
+    fn run_full(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("full", input, FULL_SIZE, 1024, &mut self.memory, answers)
+    }
+}
+
+fn run(
+    label: &'static str,
+    data: &[u8],
+    size: usize,
+    fallen: usize,
+    memory: &mut Memory,
+    answers: &mut Answers,
+) -> DayResult {
+    info!("{} start parsing", label);
+    status::set(Phase::Parsing);
+    if size > MAX_SIZE {
+        return Err(CapacityExceeded::new("day18 grid", MAX_SIZE).into());
+    }
+    memory.fall_time.fill(NEVER);
+    let mut bytes = 0;
+    let mut it = items(data, byte);
+    for (x, y) in &mut it {
+        if x >= size || y >= size || bytes >= NEVER as usize {
+            return Err(DayError::Invalid("byte out of range"));
+        }
+        let cell = &mut memory.fall_time[x + y * size];
+        if *cell == NEVER {
+            *cell = bytes as u16;
+        }
+        bytes += 1;
+    }
+    it.finish()?;
+    info!("{} read {} bytes", label, bytes);
+    info!("{} start processing", label);
+    status::set(Phase::Solving);
+
+    let part1_answer = memory.shortest_path(size, fallen);
+    info!(
+        "{} part1 answer: {}",
+        label,
+        part1_answer.unwrap_or_default()
+    );
+    answers.record(1, part1_answer.unwrap_or_default());
+
+    // Once the exit is cut off it stays cut off, so bisect on the byte count
+    let (mut lo, mut hi) = (fallen, bytes);
+    if memory.shortest_path(size, hi).is_some() {
+        return Err(DayError::Invalid("exit is never cut off"));
+    }
+    while lo + 1 < hi {
+        let mid = (lo + hi) / 2;
+        debug!("{} bisect {}..{}", label, lo, hi);
+        if memory.shortest_path(size, mid).is_some() {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    // The blocking byte is the last one to have fallen
+    let blocker = memory
+        .fall_time
+        .iter()
+        .position(|t| *t as usize == hi - 1)
+        .unwrap_or_default();
+    info!(
+        "{} part2 answer: {},{}",
+        label,
+        blocker % size,
+        blocker / size
+    );
+    answers.record(2, format_args!("{},{}", blocker % size, blocker / size));
+    Ok(())
+}
+
+fn byte(input: &[u8]) -> IResult<&[u8], (usize, usize)> {
+    map_res(
+        tuple((integer, tag(b","), integer, opt(newline))),
+        |(x, _, y, _)| Ok::<_, &[u8]>((x, y)),
+    )(input)
+}
+
+struct Memory<'s> {
+    /// Index of the byte that lands on each cell
+    fall_time: &'s mut [u16],
+    /// Cells in the order they were reached; each is queued at most once
+    queue: &'s mut [u16],
+}
+
+impl Memory<'_> {
+    /// Breadth-first search from the top left to the bottom right corner,
+    /// treating the first `fallen` bytes as walls.
+    fn shortest_path(&mut self, size: usize, fallen: usize) -> Option<usize> {
+        let mut seen = [0_u32; NUM_CELLS / 32 + 1];
+        let exit = size * size - 1;
+        self.queue[0] = 0;
+        let mut tail = 1;
+        seen[0] |= 1;
+
+        let mut head = 0;
+        let mut steps = 0;
+        while head < tail {
+            // Each pass of this loop consumes exactly one distance layer
+            let layer_end = tail;
+            while head < layer_end {
+                let cell = self.queue[head] as usize;
+                head += 1;
+                if cell == exit {
+                    return Some(steps);
+                }
+                let (x, y) = (cell % size, cell / size);
+                for (nx, ny) in [
+                    (x.wrapping_sub(1), y),
+                    (x + 1, y),
+                    (x, y.wrapping_sub(1)),
+                    (x, y + 1),
+                ] {
+                    if nx >= size || ny >= size {
+                        continue;
+                    }
+                    let next = nx + ny * size;
+                    if seen[next / 32] & (1 << (next % 32)) != 0
+                        || (self.fall_time[next] as usize) < fallen
+                    {
+                        continue;
+                    }
+                    seen[next / 32] |= 1 << (next % 32);
+                    self.queue[tail] = next as u16;
+                    tail += 1;
+                }
+            }
+            steps += 1;
+        }
+        None
+    }
+}
@@ -0,0 +1,114 @@
+use crate::{debug, info};
+
+use nom::character::complete::alpha1;
+use nom::combinator::map_res;
+use nom::combinator::opt;
+use nom::sequence::tuple;
+use nom::IResult;
+
+use super::utils::parse::{items_from, newline, non_newline, separated, List, ParseError};
+use super::utils::status::{self, Phase};
+use super::utils::{Answers, CapacityExceeded, DayResult};
+
+type ResultType = u64;
+
+const MAX_TOWELS: usize = super::config::day19::TOWELS;
+const MAX_DESIGN: usize = super::config::day19::DESIGN;
+
+pub struct Solution {}
+impl super::utils::Solution for Solution {
+    fn new(
+        _scratch: &super::utils::Scratch,
+    ) -> Result<impl super::utils::Solution, super::utils::CapacityExceeded> {
+        Ok(Self {})
+    }
+
+    fn run_sample(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("sample", input, answers)
+    }
+
+    fn run_full(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("full", input, answers)
+    }
+}
+
+fn run(label: &'static str, data: &[u8], answers: &mut Answers) -> DayResult {
+    info!("{} start parsing", label);
+    status::set(Phase::Parsing);
+    let (designs, towels) = match towels(data) {
+        Ok(parsed) => parsed,
+        Err(e) => return Err(ParseError::from_nom(data, e).into()),
+    };
+    let mut towels = towels?;
+    // Sorted, the towels starting with any given stripe form a single run
+    towels.sort_unstable();
+    info!("{} read {} towels", label, towels.len());
+    info!("{} start processing", label);
+    status::set(Phase::Solving);
+
+    let mut part1_answer = 0;
+    let mut part2_answer = 0;
+    let mut it = items_from(data, designs, design);
+    for (row, design) in (&mut it).enumerate() {
+        status::checkpoint();
+        let ways = arrangements(design, towels.as_ref())?;
+        debug!("{} {}: {} ways", label, row, ways);
+        if ways > 0 {
+            part1_answer += 1;
+        }
+        part2_answer += ways;
+    }
+    it.finish()?;
+    info!("{} part1 answer: {}", label, part1_answer);
+    answers.record(1, part1_answer);
+    info!("{} part2 answer: {}", label, part2_answer);
+    answers.record(2, part2_answer);
+    Ok(())
+}
+
+/// Counts the ways to build `design`, where `ways[i]` is the number of ways
+/// to build its first `i` stripes.
+fn arrangements(design: &[u8], towels: &[&[u8]]) -> Result<ResultType, CapacityExceeded> {
+    if design.len() > MAX_DESIGN {
+        return Err(CapacityExceeded::new("day19 design", MAX_DESIGN));
+    }
+    let mut ways = [0 as ResultType; MAX_DESIGN + 1];
+    ways[0] = 1;
+    for start in 0..design.len() {
+        if ways[start] == 0 {
+            continue;
+        }
+        let rest = &design[start..];
+        let first = towels.partition_point(|towel| towel[0] < rest[0]);
+        for towel in towels[first..]
+            .iter()
+            .take_while(|towel| towel[0] == rest[0])
+        {
+            if rest.starts_with(towel) {
+                ways[start + towel.len()] += ways[start];
+            }
+        }
+    }
+    Ok(ways[design.len()])
+}
+
+fn towels(input: &[u8]) -> IResult<&[u8], List<&[u8], MAX_TOWELS>> {
+    map_res(
+        tuple((separated(b", ", "day19 towels", alpha1), newline)),
+        |(towels, _)| Ok::<_, &[u8]>(towels),
+    )(input)
+}
+
+fn design(input: &[u8]) -> IResult<&[u8], &[u8]> {
+    map_res(tuple((non_newline, opt(newline))), |(line, _)| {
+        Ok::<_, &[u8]>(line)
+    })(input)
+}
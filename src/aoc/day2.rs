@@ -1,43 +1,62 @@
 use core::cmp::Ordering;
 
-use crate::{debug, error, info};
-use arrayvec::ArrayVec;
+use crate::{debug, info};
 
-use nom::bytes::complete::tag;
-use nom::combinator::map_res;
-use nom::combinator::opt;
-use nom::multi::fold_many1;
-use nom::sequence::tuple;
 use nom::IResult;
 
-use super::utils::parse::{integer, newline};
+use super::utils::parse::{numbers, LineReader, List, Source};
+use super::utils::status::{self, Phase};
+use super::utils::{Answers, DayResult};
 
 type ResultType = u64;
 
-const FULL: &[u8] = include_bytes!("../../input/day2.full");
-const SAMPLE: &[u8] = include_bytes!("../../input/day2.sample");
+const MAX_LINE: usize = super::config::day2::LINE;
+const MAX_LEVELS: usize = super::config::day2::LEVELS;
 
 pub struct Solution {}
 impl super::utils::Solution for Solution {
-    fn new() -> impl super::utils::Solution {
-        Self {}
+    fn new(
+        _scratch: &super::utils::Scratch,
+    ) -> Result<impl super::utils::Solution, super::utils::CapacityExceeded> {
+        Ok(Self {})
     }
 
-    fn run_sample(&mut self) {
-        run("sample", SAMPLE)
+    fn run_sample(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("sample", input, answers)
     }
 
-    fn run_full(&mut self) {
-        run("full", FULL)
+    fn run_full(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("full", input, answers)
+    }
+
+    fn run_stream(
+        &mut self,
+        label: &'static str,
+        source: &mut dyn Source,
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run(label, source, answers)
     }
 }
 
-fn run(label: &'static str, data: &[u8]) {
+fn run(label: &'static str, source: impl Source, answers: &mut Answers) -> DayResult {
     info!("{} start parsing", label);
-    let mut it = nom::combinator::iterator(data, parse_line);
+    status::set(Phase::Parsing);
+    let mut lines = LineReader::<_, MAX_LINE>::new(source);
     let mut step1_answer = 0;
     let mut step2_answer = 0;
-    for (row, line) in (&mut it).enumerate() {
+    let mut row = 0;
+    while let Some(line) = lines.parse_line(parse_line) {
+        let line = line??;
+        row += 1;
         debug!("{} {}: {}", label, row, line.len());
         if is_safe_part1(line.as_ref()) {
             step1_answer += 1;
@@ -46,17 +65,11 @@ fn run(label: &'static str, data: &[u8]) {
             step2_answer += 1;
         }
     }
-    match it.finish() {
-        Ok((r, _)) => {
-            if !r.is_empty() {
-                panic!("{} residual size: {} of {}", label, r.len(), data.len())
-            }
-        }
-        Err(_e) => error!("{} error", label),
-    }
-
     info!("{} part1 answer: {}", label, step1_answer);
+    answers.record(1, step1_answer);
     info!("{} part2 answer: {}", label, step2_answer);
+    answers.record(2, step2_answer);
+    Ok(())
 }
 
 fn is_safe_part1(report: &[ResultType]) -> bool {
@@ -129,29 +142,6 @@ fn is_safe_part2(report: &[ResultType], skip: usize) -> bool {
     true
 }
 
-fn list_number<RT, const C: usize>(input: &[u8]) -> IResult<&[u8], ArrayVec<RT, C>>
-where
-    RT: core::convert::TryFrom<i32>
-        + core::convert::TryFrom<u8>
-        + core::ops::MulAssign<RT>
-        + core::ops::AddAssign
-        + core::default::Default,
-    <RT as core::convert::TryFrom<u8>>::Error: core::fmt::Debug,
-    <RT as core::convert::TryFrom<i32>>::Error: core::fmt::Debug,
-{
-    fold_many1(
-        tuple((integer::<RT>, opt(tag(b" ")))),
-        ArrayVec::new,
-        |mut acc: ArrayVec<RT, C>, item| {
-            acc.push(item.0);
-            acc
-        },
-    )(input)
-}
-
-fn parse_line(input: &[u8]) -> IResult<&[u8], ArrayVec<ResultType, 50>> {
-    map_res(
-        nom::sequence::tuple((list_number, newline)),
-        |(answer, _)| Ok::<_, &[u8]>(answer),
-    )(input)
+fn parse_line(input: &[u8]) -> IResult<&[u8], List<ResultType, MAX_LEVELS>> {
+    numbers(b" ", "day2 levels")(input)
 }
@@ -0,0 +1,163 @@
+use crate::{debug, info};
+use arrayvec::ArrayVec;
+
+use nom::combinator::map_res;
+use nom::combinator::opt;
+use nom::IResult;
+
+use super::utils::parse::items;
+use super::utils::parse::newline;
+use super::utils::parse::non_newline;
+use super::utils::status::{self, Phase};
+use super::utils::{Answers, Bounded, CapacityExceeded, DayError, DayResult};
+
+type ResultType = u64;
+
+const MAX_SIZE: usize = super::config::day20::GRID;
+const NUM_CELLS: usize = MAX_SIZE * MAX_SIZE;
+/// Distance marker for walls and anything off the track.
+const WALL: u16 = u16::MAX;
+
+pub struct Solution<'s> {
+    distances: &'s mut [u16],
+}
+impl super::utils::Solution for Solution<'_> {
+    fn new(
+        scratch: &super::utils::Scratch,
+    ) -> Result<impl super::utils::Solution, super::utils::CapacityExceeded> {
+        let distances = scratch.slice_within(NUM_CELLS, || WALL, "day20 scratch")?;
+        Ok(Solution { distances })
+    }
+
+    fn run_sample(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("sample", input, (20, 50), self.distances, answers)
+    }
+
+    fn run_full(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("full", input, (100, 100), self.distances, answers)
+    }
+}
+
+/// `minimum_saving` holds the threshold used for each part, as the sample is
+/// too small to save the 100 picoseconds asked of the real input.
+fn run(
+    label: &'static str,
+    data: &[u8],
+    minimum_saving: (usize, usize),
+    distances: &mut [u16],
+    answers: &mut Answers,
+) -> DayResult {
+    info!("{} start parsing", label);
+    status::set(Phase::Parsing);
+    let mut grid = ArrayVec::<&[u8], MAX_SIZE>::new();
+    let mut it = items(data, grid_line);
+    for line in &mut it {
+        if line.len() > MAX_SIZE {
+            return Err(CapacityExceeded::new("day20 grid columns", MAX_SIZE).into());
+        }
+        grid.push_within(line, "day20 grid rows")?;
+    }
+    it.finish()?;
+    info!("{} read {} rows", label, grid.len());
+    info!("{} start processing", label);
+    status::set(Phase::Solving);
+
+    let Some(length) = trace(&grid, distances) else {
+        return Err(DayError::Invalid("no route from S to E"));
+    };
+    debug!("{} track length {}", label, length);
+
+    let mut part1_answer: ResultType = 0;
+    let mut part2_answer: ResultType = 0;
+    let height = grid.len() as isize;
+    let width = grid.first().map(|r| r.len()).unwrap_or_default() as isize;
+    for y in 0..height {
+        status::checkpoint();
+        for x in 0..width {
+            let from = distances[x as usize + y as usize * MAX_SIZE];
+            if from == WALL {
+                continue;
+            }
+            // Every cheat ends somewhere within a diamond of radius 20
+            for dy in -20_isize..=20 {
+                let span = 20 - dy.abs();
+                for dx in -span..=span {
+                    let (cx, cy) = (x + dx, y + dy);
+                    if cx < 0 || cy < 0 || cx >= width || cy >= height {
+                        continue;
+                    }
+                    let to = distances[cx as usize + cy as usize * MAX_SIZE];
+                    let cheat = dx.unsigned_abs() + dy.unsigned_abs();
+                    if to == WALL || (to as usize) < from as usize + cheat {
+                        continue;
+                    }
+                    let saving = to as usize - from as usize - cheat;
+                    if cheat <= 2 && saving >= minimum_saving.0 {
+                        part1_answer += 1;
+                    }
+                    if saving >= minimum_saving.1 {
+                        part2_answer += 1;
+                    }
+                }
+            }
+        }
+    }
+    info!("{} part1 answer: {}", label, part1_answer);
+    answers.record(1, part1_answer);
+    info!("{} part2 answer: {}", label, part2_answer);
+    answers.record(2, part2_answer);
+    Ok(())
+}
+
+fn grid_line(input: &[u8]) -> IResult<&[u8], &[u8]> {
+    map_res(
+        nom::sequence::tuple((non_newline, opt(newline))),
+        |(line, _)| Ok::<_, &[u8]>(line),
+    )(input)
+}
+
+/// Walks the single track from `S` to `E`, recording how far along it each
+/// cell is. Returns the length of the track.
+fn trace<const C: usize>(grid: &ArrayVec<&[u8], C>, distances: &mut [u16]) -> Option<u16> {
+    distances.fill(WALL);
+    let (mut x, mut y) = find(grid, b'S')?;
+    let mut previous = (usize::MAX, usize::MAX);
+    let mut distance = 0;
+    loop {
+        distances[x + y * MAX_SIZE] = distance;
+        if get(grid, x, y) == Some(b'E') {
+            return Some(distance);
+        }
+        let next = [
+            (x.wrapping_sub(1), y),
+            (x + 1, y),
+            (x, y.wrapping_sub(1)),
+            (x, y + 1),
+        ]
+        .into_iter()
+        .find(|&(nx, ny)| {
+            (nx, ny) != previous && matches!(get(grid, nx, ny), Some(b'.') | Some(b'E'))
+        })?;
+        previous = (x, y);
+        (x, y) = next;
+        distance += 1;
+    }
+}
+
+fn find<const C: usize>(grid: &ArrayVec<&[u8], C>, wanted: u8) -> Option<(usize, usize)> {
+    grid.iter()
+        .enumerate()
+        .find_map(|(y, row)| row.iter().position(|c| *c == wanted).map(|x| (x, y)))
+}
+
+fn get<const C: usize>(grid: &ArrayVec<&[u8], C>, x: usize, y: usize) -> Option<u8> {
+    grid.get(y).and_then(|r| r.get(x)).cloned()
+}
@@ -0,0 +1,192 @@
+use crate::{debug, info};
+use arrayvec::ArrayVec;
+
+use nom::character::complete::alphanumeric1;
+use nom::combinator::map_res;
+use nom::combinator::opt;
+use nom::IResult;
+
+use super::utils::parse::items;
+use super::utils::parse::newline;
+use super::utils::status::{self, Phase};
+use super::utils::{Answers, DayError, DayResult};
+
+type ResultType = u64;
+
+/// Directional keypad, indexed by key: `^`, `A`, `<`, `v`, `>`.
+const DIRECTIONAL_KEYS: usize = 5;
+const ACTIVATE: usize = 1;
+const MAX_ROBOTS: usize = 25;
+
+pub struct Solution {}
+impl super::utils::Solution for Solution {
+    fn new(
+        _scratch: &super::utils::Scratch,
+    ) -> Result<impl super::utils::Solution, super::utils::CapacityExceeded> {
+        Ok(Self {})
+    }
+
+    fn run_sample(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("sample", input, answers)
+    }
+
+    fn run_full(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("full", input, answers)
+    }
+}
+
+fn run(label: &'static str, data: &[u8], answers: &mut Answers) -> DayResult {
+    info!("{} start processing", label);
+    status::set(Phase::Solving);
+    let costs = Costs::new();
+
+    let mut part1_answer = 0;
+    let mut part2_answer = 0;
+    let mut it = items(data, code);
+    for code in &mut it {
+        let numeric = code
+            .iter()
+            .filter(|c| c.is_ascii_digit())
+            .fold(0, |acc: ResultType, c| acc * 10 + (c - b'0') as ResultType);
+        let (Some(part1), Some(part2)) = (costs.code(code, 2), costs.code(code, MAX_ROBOTS)) else {
+            return Err(DayError::Invalid("unknown key in code"));
+        };
+        debug!("{} {}: {} {}", label, numeric, part1, part2);
+        part1_answer += part1 * numeric;
+        part2_answer += part2 * numeric;
+    }
+    it.finish()?;
+    info!("{} part1 answer: {}", label, part1_answer);
+    answers.record(1, part1_answer);
+    info!("{} part2 answer: {}", label, part2_answer);
+    answers.record(2, part2_answer);
+    Ok(())
+}
+
+fn code(input: &[u8]) -> IResult<&[u8], &[u8]> {
+    map_res(
+        nom::sequence::tuple((alphanumeric1, opt(newline))),
+        |(code, _)| Ok::<_, &[u8]>(code),
+    )(input)
+}
+
+/// `presses[n][from][to]` is the number of human button presses needed for
+/// the robot at depth `n` to move its arm from `from` to `to` on a directional
+/// keypad and press it. Depth 0 is the human, for whom every press costs one.
+///
+/// Each robot always starts and ends on `A`, so the cost of a move is
+/// independent of everything that happened before it and the whole table
+/// costs only a few kilobytes.
+struct Costs {
+    presses: [[[ResultType; DIRECTIONAL_KEYS]; DIRECTIONAL_KEYS]; MAX_ROBOTS + 1],
+}
+
+impl Costs {
+    fn new() -> Self {
+        let mut presses = [[[1; DIRECTIONAL_KEYS]; DIRECTIONAL_KEYS]; MAX_ROBOTS + 1];
+        for depth in 1..=MAX_ROBOTS {
+            for from in 0..DIRECTIONAL_KEYS {
+                for to in 0..DIRECTIONAL_KEYS {
+                    presses[depth][from][to] = cheapest(
+                        directional_position(from),
+                        directional_position(to),
+                        (0, 0),
+                        &presses[depth - 1],
+                    );
+                }
+            }
+        }
+        Self { presses }
+    }
+
+    /// Human presses to type `code` on the numeric keypad through `robots`
+    /// intermediate directional keypads.
+    fn code(&self, code: &[u8], robots: usize) -> Option<ResultType> {
+        let mut position = numeric_position(b'A')?;
+        let mut total = 0;
+        for key in code {
+            let next = numeric_position(*key)?;
+            total += cheapest(position, next, (0, 3), &self.presses[robots]);
+            position = next;
+        }
+        Some(total)
+    }
+}
+
+/// Cheapest way to move an arm from `from` to `to` and press the key there,
+/// given the costs one keypad further up the chain.
+///
+/// Turning more than once never helps, so only the two routes that do all of
+/// one axis first are considered, skipping any that would cross `gap`.
+fn cheapest(
+    from: (isize, isize),
+    to: (isize, isize),
+    gap: (isize, isize),
+    costs: &[[ResultType; DIRECTIONAL_KEYS]; DIRECTIONAL_KEYS],
+) -> ResultType {
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let horizontal = if dx < 0 { 2 } else { 4 };
+    let vertical = if dy < 0 { 0 } else { 3 };
+
+    let mut best = ResultType::MAX;
+    for horizontal_first in [true, false] {
+        let corner = if horizontal_first {
+            (to.0, from.1)
+        } else {
+            (from.0, to.1)
+        };
+        if corner == gap {
+            continue;
+        }
+        let mut keys = ArrayVec::<usize, 8>::new();
+        let runs = if horizontal_first {
+            [(horizontal, dx), (vertical, dy)]
+        } else {
+            [(vertical, dy), (horizontal, dx)]
+        };
+        for (key, count) in runs {
+            for _ in 0..count.unsigned_abs() {
+                keys.push(key);
+            }
+        }
+        keys.push(ACTIVATE);
+        let mut cost = 0;
+        let mut arm = ACTIVATE;
+        for key in keys {
+            cost += costs[arm][key];
+            arm = key;
+        }
+        best = best.min(cost);
+    }
+    best
+}
+
+fn directional_position(key: usize) -> (isize, isize) {
+    [(1, 0), (2, 0), (0, 1), (1, 1), (2, 1)][key]
+}
+
+fn numeric_position(key: u8) -> Option<(isize, isize)> {
+    let position = match key {
+        b'7' => (0, 0),
+        b'8' => (1, 0),
+        b'9' => (2, 0),
+        b'4' => (0, 1),
+        b'5' => (1, 1),
+        b'6' => (2, 1),
+        b'1' => (0, 2),
+        b'2' => (1, 2),
+        b'3' => (2, 2),
+        b'0' => (1, 3),
+        b'A' => (2, 3),
+        _ => return None,
+    };
+    Some(position)
+}
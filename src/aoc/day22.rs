@@ -0,0 +1,126 @@
+use crate::{debug, info};
+
+use nom::combinator::map_res;
+use nom::combinator::opt;
+use nom::IResult;
+
+use super::utils::parse::{integer, items, newline};
+use super::utils::status::{self, Phase};
+use super::utils::{Answers, DayResult};
+
+type ResultType = u64;
+
+const ROUNDS: usize = 2000;
+/// Four price changes, each in -9..=9
+const NUM_SEQUENCES: usize = 19 * 19 * 19 * 19;
+/// A tally of every sequence at once would need over 250K of RAM, so the
+/// sequences are split into this many slices, replaying the buyers for each.
+const PASSES: usize = super::config::day22::PASSES;
+const SLICE: usize = NUM_SEQUENCES.div_ceil(PASSES);
+
+pub struct Solution<'s> {
+    tally: Tally<'s>,
+}
+impl super::utils::Solution for Solution<'_> {
+    fn new(
+        scratch: &super::utils::Scratch,
+    ) -> Result<impl super::utils::Solution, super::utils::CapacityExceeded> {
+        let bananas = scratch.slice_within(SLICE, || 0, "day22 scratch")?;
+        let sold = scratch.slice_within(SLICE / 32 + 1, || 0, "day22 scratch")?;
+        Ok(Solution {
+            tally: Tally { bananas, sold },
+        })
+    }
+
+    fn run_sample(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("sample", input, &mut self.tally, answers)
+    }
+
+    fn run_full(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("full", input, &mut self.tally, answers)
+    }
+}
+
+fn run(label: &'static str, data: &[u8], tally: &mut Tally, answers: &mut Answers) -> DayResult {
+    info!("{} start processing", label);
+    status::set(Phase::Solving);
+    let mut part1_answer: ResultType = 0;
+    let mut it = items(data, secret);
+    for secret in &mut it {
+        status::checkpoint();
+        let last = (0..ROUNDS).fold(secret, |secret, _| next(secret));
+        part1_answer += last as ResultType;
+    }
+    it.finish()?;
+    info!("{} part1 answer: {}", label, part1_answer);
+    answers.record(1, part1_answer);
+
+    let mut part2_answer = 0;
+    for pass in 0..PASSES {
+        tally.bananas.fill(0);
+        let first = pass * SLICE;
+        for secret in &mut items(data, secret) {
+            status::checkpoint();
+            tally.add_buyer(secret, first);
+        }
+        let best = tally.bananas.iter().max().cloned().unwrap_or_default();
+        debug!("{} pass {}: best {}", label, pass, best);
+        part2_answer = part2_answer.max(best);
+    }
+    info!("{} part2 answer: {}", label, part2_answer);
+    answers.record(2, part2_answer);
+    Ok(())
+}
+
+fn secret(input: &[u8]) -> IResult<&[u8], u32> {
+    map_res(
+        nom::sequence::tuple((integer, opt(newline))),
+        |(secret, _)| Ok::<_, &[u8]>(secret),
+    )(input)
+}
+
+fn next(secret: u32) -> u32 {
+    const PRUNE: u32 = 0xFF_FFFF;
+    let secret = (secret ^ (secret << 6)) & PRUNE;
+    let secret = (secret ^ (secret >> 5)) & PRUNE;
+    (secret ^ (secret << 11)) & PRUNE
+}
+
+/// Bananas earned by each sequence of price changes in the current slice.
+struct Tally<'s> {
+    bananas: &'s mut [u16],
+    /// Sequences already sold on by the buyer being replayed
+    sold: &'s mut [u32],
+}
+
+impl Tally<'_> {
+    fn add_buyer(&mut self, secret: u32, first: usize) {
+        self.sold.fill(0);
+        let mut secret = secret;
+        let mut price = secret % 10;
+        // Base-19 digits of the last four changes
+        let mut sequence = 0;
+        for round in 0..ROUNDS {
+            secret = next(secret);
+            let next_price = secret % 10;
+            sequence = (sequence * 19 + (next_price + 9 - price) as usize) % NUM_SEQUENCES;
+            price = next_price;
+            if round < 3 || sequence < first || sequence >= first + SLICE {
+                continue;
+            }
+            let idx = sequence - first;
+            if self.sold[idx / 32] & (1 << (idx % 32)) == 0 {
+                self.sold[idx / 32] |= 1 << (idx % 32);
+                self.bananas[idx] += price as u16;
+            }
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use core::fmt::Write;
+
+use crate::{debug, info};
+use arrayvec::{ArrayString, ArrayVec};
+
+use nom::bytes::complete::{tag, take};
+use nom::combinator::map_res;
+use nom::combinator::opt;
+use nom::sequence::tuple;
+use nom::IResult;
+
+use super::utils::parse::items;
+use super::utils::parse::newline;
+use super::utils::status::{self, Phase};
+use super::utils::{Answers, Bounded, DayResult};
+
+type ResultType = u64;
+
+/// Computer names are two lowercase letters.
+const NUM_COMPUTERS: usize = 26 * 26;
+/// Every computer in the real input has 13 connections.
+const MAX_DEGREE: usize = super::config::day23::DEGREE;
+
+/// Links of each computer, indexed by name.
+type Network = [ArrayVec<u16, MAX_DEGREE>];
+
+pub struct Solution<'s> {
+    network: &'s mut Network,
+}
+impl super::utils::Solution for Solution<'_> {
+    fn new(
+        scratch: &super::utils::Scratch,
+    ) -> Result<impl super::utils::Solution, super::utils::CapacityExceeded> {
+        let network = scratch.slice_within(NUM_COMPUTERS, ArrayVec::new, "day23 scratch")?;
+        Ok(Solution { network })
+    }
+
+    fn run_sample(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("sample", input, self.network, answers)
+    }
+
+    fn run_full(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("full", input, self.network, answers)
+    }
+}
+
+fn run(
+    label: &'static str,
+    data: &[u8],
+    network: &mut Network,
+    answers: &mut Answers,
+) -> DayResult {
+    info!("{} start parsing", label);
+    status::set(Phase::Parsing);
+    for links in network.iter_mut() {
+        links.clear();
+    }
+    let mut it = items(data, connection);
+    for (a, b) in &mut it {
+        network[a].push_within(b as u16, "day23 links per computer")?;
+        network[b].push_within(a as u16, "day23 links per computer")?;
+    }
+    it.finish()?;
+    for links in network.iter_mut() {
+        links.sort_unstable();
+    }
+    info!("{} start processing", label);
+    status::set(Phase::Solving);
+
+    let mut part1_answer: ResultType = 0;
+    for a in 0..NUM_COMPUTERS {
+        for &b in network[a].iter().filter(|b| **b as usize > a) {
+            for &c in network[b as usize].iter().filter(|c| **c > b) {
+                if !network[a].contains(&c) {
+                    continue;
+                }
+                if [a as u16, b, c].iter().any(|n| name(*n)[0] == b't') {
+                    part1_answer += 1;
+                }
+            }
+        }
+    }
+    info!("{} part1 answer: {}", label, part1_answer);
+    answers.record(1, part1_answer);
+
+    let mut best = ArrayVec::<u16, { MAX_DEGREE + 1 }>::new();
+    for computer in 0..NUM_COMPUTERS {
+        status::checkpoint();
+        let links = &network[computer];
+        if links.len() < best.len() {
+            continue;
+        }
+        // Adjacency between this computer's neighbours, as bitmasks
+        let mut adjacent = [0_u32; MAX_DEGREE];
+        for (i, a) in links.iter().enumerate() {
+            for (j, b) in links.iter().enumerate() {
+                if i != j && network[*a as usize].contains(b) {
+                    adjacent[i] |= 1 << j;
+                }
+            }
+        }
+        let all = (1_u32 << links.len()) - 1;
+        let clique = largest_clique(&adjacent, 0, all);
+        if clique.count_ones() as usize + 1 > best.len() {
+            best.clear();
+            best.push(computer as u16);
+            best.extend(
+                (0..links.len())
+                    .filter(|i| clique & (1 << i) != 0)
+                    .map(|i| links[i]),
+            );
+            debug!("{} clique of {} via {}", label, best.len(), computer);
+        }
+    }
+    best.sort_unstable();
+    let mut part2_answer = ArrayString::<{ 3 * (MAX_DEGREE + 1) }>::new();
+    for (i, computer) in best.iter().enumerate() {
+        let separator = if i == 0 { "" } else { "," };
+        let [a, b] = name(*computer);
+        let _ = write!(part2_answer, "{}{}{}", separator, a as char, b as char);
+    }
+    info!("{} part2 answer: {}", label, part2_answer.as_str());
+    answers.record(2, part2_answer.as_str());
+    Ok(())
+}
+
+/// Largest clique made of `chosen` plus members of `candidates`, by
+/// branching on whether the lowest candidate is included.
+fn largest_clique(adjacent: &[u32; MAX_DEGREE], chosen: u32, candidates: u32) -> u32 {
+    if candidates == 0 {
+        return chosen;
+    }
+    let next = candidates.trailing_zeros();
+    let with = largest_clique(
+        adjacent,
+        chosen | (1 << next),
+        candidates & adjacent[next as usize],
+    );
+    // Only worth excluding `next` if the rest could still do better
+    let without_candidates = candidates & !(1 << next);
+    if (chosen | without_candidates).count_ones() <= with.count_ones() {
+        return with;
+    }
+    let without = largest_clique(adjacent, chosen, without_candidates);
+    if without.count_ones() > with.count_ones() {
+        without
+    } else {
+        with
+    }
+}
+
+fn name(computer: u16) -> [u8; 2] {
+    [b'a' + (computer / 26) as u8, b'a' + (computer % 26) as u8]
+}
+
+fn computer(input: &[u8]) -> IResult<&[u8], usize> {
+    map_res(take(2_usize), |name: &[u8]| {
+        if name.iter().all(u8::is_ascii_lowercase) {
+            Ok(((name[0] - b'a') as usize) * 26 + (name[1] - b'a') as usize)
+        } else {
+            Err(name)
+        }
+    })(input)
+}
+
+fn connection(input: &[u8]) -> IResult<&[u8], (usize, usize)> {
+    map_res(
+        tuple((computer, tag(b"-"), computer, opt(newline))),
+        |(a, _, b, _)| Ok::<_, &[u8]>((a, b)),
+    )(input)
+}
@@ -0,0 +1,243 @@
+use core::fmt::Write;
+
+use crate::{debug, info};
+use arrayvec::{ArrayString, ArrayVec};
+
+use nom::branch::alt;
+use nom::bytes::complete::{tag, take};
+use nom::combinator::map_res;
+use nom::combinator::opt;
+use nom::sequence::tuple;
+use nom::IResult;
+
+use super::utils::parse::{integer, items, newline};
+use super::utils::status::{self, Phase};
+use super::utils::{Answers, Bounded, CapacityExceeded, DayError, DayResult};
+
+type ResultType = u64;
+
+const MAX_WIRES: usize = super::config::day24::WIRES;
+const MAX_GATES: usize = super::config::day24::GATES;
+/// The adder has had exactly four pairs of outputs swapped.
+const MAX_SWAPPED: usize = 8;
+
+pub struct Solution {}
+impl super::utils::Solution for Solution {
+    fn new(
+        _scratch: &super::utils::Scratch,
+    ) -> Result<impl super::utils::Solution, super::utils::CapacityExceeded> {
+        Ok(Self {})
+    }
+
+    fn run_sample(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("sample", input, answers)
+    }
+
+    fn run_full(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("full", input, answers)
+    }
+}
+
+fn run(label: &'static str, data: &[u8], answers: &mut Answers) -> DayResult {
+    info!("{} start parsing", label);
+    status::set(Phase::Parsing);
+    let mut circuit = Circuit::default();
+    let mut it = items(data, initial);
+    for (name, value) in &mut it {
+        let wire = circuit.wire(name)?;
+        circuit.values[wire] = Some(value != 0);
+    }
+    let mut it = it.then(gate)?;
+    for (a, op, b, out) in &mut it {
+        let (a, b, out) = (circuit.wire(a)?, circuit.wire(b)?, circuit.wire(out)?);
+        circuit
+            .gates
+            .push_within(Gate { a, op, b, out }, "day24 gates")?;
+    }
+    it.finish()?;
+    info!(
+        "{} read {} wires, {} gates",
+        label,
+        circuit.names.len(),
+        circuit.gates.len()
+    );
+    info!("{} start processing", label);
+    status::set(Phase::Solving);
+
+    circuit.settle();
+    let mut part1_answer: ResultType = 0;
+    for (wire, name) in circuit.names.iter().enumerate() {
+        if name[0] != b'z' {
+            continue;
+        }
+        let bit = output_bit(name).ok_or(DayError::Invalid("z wire is not z00 to z63"))?;
+        match circuit.values[wire] {
+            Some(true) => part1_answer |= 1 << bit,
+            Some(false) => {}
+            None => return Err(DayError::Invalid("z wire never set")),
+        }
+    }
+    info!("{} part1 answer: {}", label, part1_answer);
+    answers.record(1, part1_answer);
+
+    let mut swapped = circuit.miswired();
+    swapped.sort_unstable();
+    let mut part2_answer = ArrayString::<{ 4 * MAX_SWAPPED }>::new();
+    for (i, name) in swapped.iter().enumerate() {
+        let separator = if i == 0 { "" } else { "," };
+        let [a, b, c] = *name;
+        let _ = write!(
+            part2_answer,
+            "{}{}{}{}",
+            separator, a as char, b as char, c as char
+        );
+    }
+    info!("{} part2 answer: {}", label, part2_answer.as_str());
+    answers.record(2, part2_answer.as_str());
+    Ok(())
+}
+
+/// The bit of the output that wire `z<nn>` gives.
+fn output_bit(name: &[u8; 3]) -> Option<u32> {
+    let [_, tens, units] = *name;
+    if !tens.is_ascii_digit() || !units.is_ascii_digit() {
+        return None;
+    }
+    let bit = (tens - b'0') as u32 * 10 + (units - b'0') as u32;
+    (bit < ResultType::BITS).then_some(bit)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Op {
+    And,
+    Or,
+    Xor,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Gate {
+    a: usize,
+    op: Op,
+    b: usize,
+    out: usize,
+}
+
+#[derive(Default)]
+struct Circuit {
+    names: ArrayVec<[u8; 3], MAX_WIRES>,
+    values: ArrayVec<Option<bool>, MAX_WIRES>,
+    gates: ArrayVec<Gate, MAX_GATES>,
+}
+
+impl Circuit {
+    fn wire(&mut self, name: [u8; 3]) -> Result<usize, CapacityExceeded> {
+        if let Some(wire) = self.names.iter().position(|n| *n == name) {
+            return Ok(wire);
+        }
+        self.names.push_within(name, "day24 wires")?;
+        self.values.push(None);
+        Ok(self.names.len() - 1)
+    }
+
+    /// Propagates values until no gate can fire; the gates are not listed in
+    /// any particular order, so this takes at most one pass per gate.
+    fn settle(&mut self) {
+        for pass in 0..self.gates.len() {
+            let mut changed = false;
+            for gate in self.gates.iter() {
+                if self.values[gate.out].is_some() {
+                    continue;
+                }
+                let (Some(a), Some(b)) = (self.values[gate.a], self.values[gate.b]) else {
+                    continue;
+                };
+                self.values[gate.out] = Some(match gate.op {
+                    Op::And => a && b,
+                    Op::Or => a || b,
+                    Op::Xor => a ^ b,
+                });
+                changed = true;
+            }
+            if !changed {
+                debug!("settled after {} passes", pass);
+                break;
+            }
+        }
+    }
+
+    /// Outputs that break the shape of a ripple-carry adder. Each full adder
+    /// is `x ^ y -> s`, `x & y -> c1`, `s ^ carry -> z`, `s & carry -> c2`
+    /// and `c1 | c2 -> carry`, apart from the half adder on bit 0 and the
+    /// final carry, which becomes the top bit of `z` directly.
+    fn miswired(&self) -> ArrayVec<[u8; 3], MAX_SWAPPED> {
+        let is_input = |wire: usize| matches!(self.names[wire][0], b'x' | b'y');
+        let is_first_bit = |wire: usize| &self.names[wire][1..] == b"00";
+        let feeds = |wire: usize, op: Op| {
+            self.gates
+                .iter()
+                .any(|g| g.op == op && (g.a == wire || g.b == wire))
+        };
+        let top_bit = self
+            .names
+            .iter()
+            .filter(|n| n[0] == b'z')
+            .max()
+            .cloned()
+            .unwrap_or_default();
+
+        let mut wrong = ArrayVec::new();
+        for gate in self.gates.iter() {
+            let out = self.names[gate.out];
+            let from_inputs = is_input(gate.a) && is_input(gate.b);
+            let half_adder = from_inputs && is_first_bit(gate.a);
+            let misplaced = match gate.op {
+                _ if out[0] == b'z' && out != top_bit && gate.op != Op::Xor => true,
+                Op::Xor if !from_inputs => out[0] != b'z',
+                Op::Xor if !half_adder => !feeds(gate.out, Op::Xor),
+                Op::And if !half_adder => !feeds(gate.out, Op::Or),
+                _ => false,
+            };
+            if misplaced && !wrong.contains(&out) && wrong.try_push(out).is_err() {
+                break;
+            }
+        }
+        wrong
+    }
+}
+
+fn name(input: &[u8]) -> IResult<&[u8], [u8; 3]> {
+    map_res(take(3_usize), <[u8; 3]>::try_from)(input)
+}
+
+fn initial(input: &[u8]) -> IResult<&[u8], ([u8; 3], u8)> {
+    map_res(
+        tuple((name, tag(b": "), integer, newline)),
+        |(name, _, value, _)| Ok::<_, &[u8]>((name, value)),
+    )(input)
+}
+
+fn op(input: &[u8]) -> IResult<&[u8], Op> {
+    alt((
+        map_res(tag(b" AND "), |_| Ok::<_, &[u8]>(Op::And)),
+        map_res(tag(b" XOR "), |_| Ok::<_, &[u8]>(Op::Xor)),
+        map_res(tag(b" OR "), |_| Ok::<_, &[u8]>(Op::Or)),
+    ))(input)
+}
+
+/// A gate as written, its wires by name: `Gate` is taken by the wired-up form.
+type NamedGate = ([u8; 3], Op, [u8; 3], [u8; 3]);
+
+fn gate(input: &[u8]) -> IResult<&[u8], NamedGate> {
+    map_res(
+        tuple((name, op, name, tag(b" -> "), name, opt(newline))),
+        |(a, op, b, _, out, _)| Ok::<_, &[u8]>((a, op, b, out)),
+    )(input)
+}
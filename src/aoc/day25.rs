@@ -0,0 +1,94 @@
+use crate::{debug, info};
+use arrayvec::ArrayVec;
+
+use super::utils::parse::{items, section};
+use super::utils::status::{self, Phase};
+use super::utils::{Answers, Bounded, DayResult};
+
+type ResultType = u64;
+
+const COLUMNS: usize = 5;
+/// Pin heights leave room for this many rows between the top and bottom.
+const SPACE: u8 = 5;
+const MAX_SCHEMATICS: usize = super::config::day25::SCHEMATICS;
+
+pub struct Solution {}
+impl super::utils::Solution for Solution {
+    fn new(
+        _scratch: &super::utils::Scratch,
+    ) -> Result<impl super::utils::Solution, super::utils::CapacityExceeded> {
+        Ok(Self {})
+    }
+
+    fn run_sample(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("sample", input, answers)
+    }
+
+    fn run_full(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("full", input, answers)
+    }
+}
+
+fn run(label: &'static str, data: &[u8], answers: &mut Answers) -> DayResult {
+    info!("{} start parsing", label);
+    status::set(Phase::Parsing);
+    let mut locks = ArrayVec::<[u8; COLUMNS], MAX_SCHEMATICS>::new();
+    let mut keys = ArrayVec::<[u8; COLUMNS], MAX_SCHEMATICS>::new();
+
+    // Each schematic is a block of rows, separated by blank lines
+    let mut it = items(data, section);
+    for schematic in &mut it {
+        let mut heights = [0_u8; COLUMNS];
+        let mut is_lock = None;
+        let rows = schematic
+            .split(|c| *c == b'\n')
+            .map(|line| line.strip_suffix(b"\r").unwrap_or(line));
+        for row in rows {
+            if is_lock.is_none() {
+                is_lock = Some(row.iter().all(|c| *c == b'#'));
+            }
+            for (height, c) in heights.iter_mut().zip(row.iter()) {
+                if *c == b'#' {
+                    *height += 1;
+                }
+            }
+        }
+        // Heights exclude the solid top (lock) or bottom (key) row
+        let heights = heights.map(|h| h.saturating_sub(1));
+        match is_lock {
+            None => continue,
+            Some(true) => locks.push_within(heights, "day25 locks")?,
+            Some(false) => keys.push_within(heights, "day25 keys")?,
+        }
+    }
+    it.finish()?;
+    info!("{} read {} locks, {} keys", label, locks.len(), keys.len());
+    info!("{} start processing", label);
+    status::set(Phase::Solving);
+
+    let mut part1_answer: ResultType = 0;
+    for lock in locks.iter() {
+        for key in keys.iter() {
+            if lock.iter().zip(key.iter()).all(|(l, k)| l + k <= SPACE) {
+                part1_answer += 1;
+            }
+        }
+    }
+    debug!(
+        "{} {} of {} pairs fit",
+        label,
+        part1_answer,
+        locks.len() * keys.len()
+    );
+    info!("{} part1 answer: {}", label, part1_answer);
+    answers.record(1, part1_answer);
+    Ok(())
+}
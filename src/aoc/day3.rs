@@ -2,62 +2,88 @@ use crate::info;
 
 use nom::branch::alt;
 use nom::bytes::complete::tag;
-use nom::bytes::complete::take;
-use nom::combinator::iterator;
 use nom::combinator::map_res;
 use nom::IResult;
 
 use super::utils::parse::integer;
+use super::utils::parse::matches;
+use super::utils::parse::{LineReader, Source};
+use super::utils::status::{self, Phase};
+use super::utils::{Answers, DayResult};
 
 type ResultType = u64;
 
-const FULL: &[u8] = include_bytes!("../../input/day3.full");
-const SAMPLE: &[u8] = include_bytes!("../../input/day3.sample");
+/// Real inputs are a handful of lines, each a few thousand bytes long.
+const MAX_LINE: usize = super::config::day3::LINE;
 
 pub struct Solution {}
 impl super::utils::Solution for Solution {
-    fn new() -> impl super::utils::Solution {
-        Self {}
+    fn new(
+        _scratch: &super::utils::Scratch,
+    ) -> Result<impl super::utils::Solution, super::utils::CapacityExceeded> {
+        Ok(Self {})
     }
 
-    fn run_sample(&mut self) {
-        run("sample", SAMPLE)
+    fn run_sample(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("sample", input, answers)
     }
 
-    fn run_full(&mut self) {
-        run("full", FULL)
+    fn run_full(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("full", input, answers)
+    }
+
+    fn run_stream(
+        &mut self,
+        label: &'static str,
+        source: &mut dyn Source,
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run(label, source, answers)
     }
 }
 
-fn run(label: &'static str, data: &[u8]) {
+fn run(label: &'static str, source: impl Source, answers: &mut Answers) -> DayResult {
     info!("{} start parsing", label);
-    let mut it = iterator(data, parse);
+    status::set(Phase::Parsing);
+    let mut lines = LineReader::<_, MAX_LINE>::new(source);
     let mut step1_answer = 0;
     let mut step2_answer = 0;
     let mut enabled = true;
-    for v in &mut it {
-        match v {
-            Op::Noop => {}
-            Op::Mul(mul) => {
-                step1_answer += mul;
-                if enabled {
-                    step2_answer += mul;
+    while let Some(line) = lines.next_line() {
+        let line = line?;
+        for v in matches(line, parse) {
+            match v {
+                Op::Mul(mul) => {
+                    step1_answer += mul;
+                    if enabled {
+                        step2_answer += mul;
+                    }
+                }
+                Op::Disable => {
+                    enabled = false;
+                }
+                Op::Enable => {
+                    enabled = true;
                 }
-            }
-            Op::Disable => {
-                enabled = false;
-            }
-            Op::Enable => {
-                enabled = true;
             }
         }
     }
     info!("{} step1 answer = {}", label, step1_answer);
+    answers.record(1, step1_answer);
     info!("{} step2 answer = {}", label, step2_answer);
+    answers.record(2, step2_answer);
+    Ok(())
 }
 
 enum Op {
-    Noop,
     Mul(ResultType),
     Enable,
     Disable,
@@ -82,10 +108,7 @@ fn on(input: &[u8]) -> IResult<&[u8], Op> {
 fn off(input: &[u8]) -> IResult<&[u8], Op> {
     map_res(tag(b"don't()"), |_| Ok::<_, &[u8]>(Op::Disable))(input)
 }
-fn skip(input: &[u8]) -> IResult<&[u8], Op> {
-    map_res(take(1_usize), |_| Ok::<_, &[u8]>(Op::Noop))(input)
-}
 
 fn parse(input: &[u8]) -> IResult<&[u8], Op> {
-    alt((mul, on, off, skip))(input)
+    alt((mul, on, off))(input)
 }
@@ -1,43 +1,56 @@
 use crate::{debug, info};
 use arrayvec::ArrayVec;
 
-use nom::combinator::iterator;
 use nom::combinator::map_res;
 use nom::IResult;
 
-use super::utils::parse::{newline, non_newline};
+use super::utils::parse::{items, newline, non_newline};
+use super::utils::status::{self, Phase};
+use super::utils::{Answers, Bounded, DayResult};
 
-const FULL: &[u8] = include_bytes!("../../input/day4.full");
-const SAMPLE: &[u8] = include_bytes!("../../input/day4.sample");
+const MAX_GRID: usize = super::config::day4::GRID;
 
 pub struct Solution {}
 impl super::utils::Solution for Solution {
-    fn new() -> impl super::utils::Solution {
-        Self {}
+    fn new(
+        _scratch: &super::utils::Scratch,
+    ) -> Result<impl super::utils::Solution, super::utils::CapacityExceeded> {
+        Ok(Self {})
     }
 
-    fn run_sample(&mut self) {
-        run("sample", SAMPLE)
+    fn run_sample(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("sample", input, answers)
     }
 
-    fn run_full(&mut self) {
-        run("full", FULL)
+    fn run_full(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("full", input, answers)
     }
 }
 
-fn run(label: &'static str, data: &[u8]) {
+fn run(label: &'static str, data: &[u8], answers: &mut Answers) -> DayResult {
     info!("{} start parsing", label);
+    status::set(Phase::Parsing);
 
-    let mut grid = ArrayVec::<&[u8], 200>::new();
-    let mut it = iterator(data, grid_line);
+    let mut grid = ArrayVec::<&[u8], MAX_GRID>::new();
+    let mut it = items(data, grid_line);
     for line in &mut it {
-        grid.push(line);
+        grid.push_within(line, "day4 grid rows")?;
         if line.is_empty() {
             info!("Manual abort");
             break;
         }
     }
+    it.finish()?;
     info!("{} start processing", label);
+    status::set(Phase::Solving);
     let all_directions = [
         (0, -1),  //Direction::N,
         (0, 1),   //Direction::S,
@@ -62,6 +75,7 @@ fn run(label: &'static str, data: &[u8]) {
         }
     }
     info!("{} step1 answer = {}", label, step1_answer);
+    answers.record(1, step1_answer);
     let mut step2_answer = 0;
     for (sy, row) in grid.iter().enumerate() {
         for (sx, c) in row.iter().enumerate() {
@@ -87,10 +101,12 @@ fn run(label: &'static str, data: &[u8]) {
     }
     // Implement for problem
     info!("{} step2 answer = {}", label, step2_answer / 2);
+    answers.record(2, step2_answer / 2);
+    Ok(())
 }
 
 fn walk(
-    grid: &ArrayVec<&[u8], 200>,
+    grid: &ArrayVec<&[u8], MAX_GRID>,
     sx: isize,
     sy: isize,
     dx: isize,
@@ -1,153 +1,162 @@
 use core::cmp::Ordering;
 
-use crate::{debug, error, info};
-use arrayvec::ArrayVec;
-use nom::{
-    branch::alt,
-    bytes::complete::tag,
-    combinator::{iterator, map_res},
-    multi::fold_many1,
-    sequence::tuple,
-    IResult,
-};
-
-use super::utils::parse::{integer, newline};
+use crate::{debug, info};
+use nom::{branch::alt, combinator::map, IResult};
+
+use super::utils::parse::{numbers, rule, LineReader, List, Source};
+use super::utils::status::{self, Phase};
+use super::utils::{Answers, CapacityExceeded, DayResult};
 
 type ResultType = u64;
 
-const FULL: &[u8] = include_bytes!("../../input/day5.full");
-const SAMPLE: &[u8] = include_bytes!("../../input/day5.sample");
+const MAX_LINE: usize = super::config::day5::LINE;
+const MAX_PAGES: usize = super::config::day5::PAGES;
+/// Page numbers have at most 2 digits.
+const PAGE_NUMBERS: usize = 100;
 
 pub struct Solution {}
 impl super::utils::Solution for Solution {
-    fn new() -> impl super::utils::Solution {
-        Self {}
+    fn new(
+        _scratch: &super::utils::Scratch,
+    ) -> Result<impl super::utils::Solution, super::utils::CapacityExceeded> {
+        Ok(Self {})
+    }
+
+    fn run_sample(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("sample", input, answers)
     }
 
-    fn run_sample(&mut self) {
-        run("sample", SAMPLE)
+    fn run_full(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("full", input, answers)
     }
 
-    fn run_full(&mut self) {
-        run("full", FULL)
+    fn run_stream(
+        &mut self,
+        label: &'static str,
+        source: &mut dyn Source,
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run(label, source, answers)
     }
 }
 
-fn run(label: &'static str, data: &[u8]) {
+/// Every ordering rule comes before the first update, so each update is
+/// checked as soon as it is read rather than kept.
+fn run(label: &'static str, source: impl Source, answers: &mut Answers) -> DayResult {
     info!("{} start parsing", label);
+    status::set(Phase::Parsing);
 
-    let mut ordering = ArrayVec::<_, 1200>::new();
-    let mut updates = ArrayVec::<_, 256>::new();
-    let mut it = iterator(data, parse);
-    for (row, step) in (&mut it).enumerate() {
+    let mut rules = Rules::new();
+    let mut orderings = 0;
+    let mut updates = 0;
+    let mut part1_answer = 0;
+    let mut part2_answer = 0;
+    let mut lines = LineReader::<_, MAX_LINE>::new(source);
+    let mut row = 0;
+    while let Some(step) = lines.parse_line(parse) {
+        row += 1;
         match step {
-            Step::Ordering(lhs, rhs) => {
+            Ok(Step::Ordering(lhs, rhs)) => {
                 debug!("{} {}: ordering", label, row);
-                ordering.push((lhs, rhs));
+                rules.insert(lhs, rhs)?;
+                orderings += 1;
             }
-            Step::Update(u) => {
+            Ok(Step::Update(update)) => {
+                let update = update?;
                 debug!("{} {}: update", label, row);
-                updates.push(u);
+                updates += 1;
+                if is_correct(update.as_ref(), &rules) {
+                    part1_answer += update[update.len() / 2];
+                } else {
+                    let mut fixed = update.clone();
+                    fix(fixed.as_mut(), &rules);
+                    part2_answer += fixed[fixed.len() / 2];
+                }
             }
+            Err(e) => return Err(e.into()),
         }
     }
-    match it.finish() {
-        Ok((r, _)) => {
-            if !r.is_empty() {
-                panic!("{} residual size: {}", label, r.len())
-            }
+    debug!("{} orderings: {}", label, orderings);
+    debug!("{} updates: {}", label, updates);
+
+    info!("{} part1 answer = {}", label, part1_answer);
+    answers.record(1, part1_answer);
+    info!("{} part2 answer = {}", label, part2_answer);
+    answers.record(2, part2_answer);
+    Ok(())
+}
+
+/// The ordering rules, as a bit for each pair of page numbers that is set
+/// when the first must come before the second.
+struct Rules {
+    bits: [u32; (PAGE_NUMBERS * PAGE_NUMBERS).div_ceil(32)],
+}
+
+impl Rules {
+    fn new() -> Self {
+        Self {
+            bits: [0; (PAGE_NUMBERS * PAGE_NUMBERS).div_ceil(32)],
         }
-        Err(_e) => error!("{} error", label),
     }
-    debug!("{} orderings: {}", label, ordering.len());
-    debug!("{} updates: {}", label, updates.len());
 
-    info!("{} start processing", label);
+    fn insert(&mut self, before: ResultType, after: ResultType) -> DayResult {
+        let bit = Self::bit(before, after)
+            .ok_or(CapacityExceeded::new("day5 page numbers", PAGE_NUMBERS - 1))?;
+        self.bits[bit / 32] |= 1 << (bit % 32);
+        Ok(())
+    }
 
-    let mut part1_answer = 0;
-    for update in updates.iter() {
-        let correct = is_correct(update.as_ref(), ordering.as_ref());
-        if correct {
-            let mid = update.get(update.len() / 2).unwrap();
-            part1_answer += mid;
-        }
+    /// Whether `before` must come before `after`; pages no rule mentions
+    /// may come in either order.
+    fn contains(&self, before: ResultType, after: ResultType) -> bool {
+        Self::bit(before, after).is_some_and(|bit| self.bits[bit / 32] & (1 << (bit % 32)) != 0)
     }
-    // Implement for problem
-    info!("{} part1 answer = {}", label, part1_answer);
 
-    let mut part2_answer = 0;
-    for update in updates.iter() {
-        let correct = is_correct(update.as_ref(), ordering.as_ref());
-        if !correct {
-            let mut fixed = update.clone();
-            fix(fixed.as_mut(), ordering.as_ref());
-            let mid = fixed.get(update.len() / 2).unwrap();
-            part2_answer += mid;
-        }
+    fn bit(before: ResultType, after: ResultType) -> Option<usize> {
+        let (before, after) = (before as usize, after as usize);
+        (before < PAGE_NUMBERS && after < PAGE_NUMBERS).then_some(before * PAGE_NUMBERS + after)
     }
-    // Implement for problem
-    info!("{} part2 answer = {}", label, part2_answer);
 }
 
-fn is_correct(update: &[ResultType], rules: &[(ResultType, ResultType)]) -> bool {
-    for (i, page) in update.iter().enumerate() {
-        for j in 0..i {
-            let probe = update.get(j).unwrap();
-            if rules.contains(&(*page, *probe)) {
-                return false;
-            }
-        }
-    }
-    true
+fn is_correct(update: &[ResultType], rules: &Rules) -> bool {
+    update.iter().enumerate().all(|(i, page)| {
+        update[..i]
+            .iter()
+            .all(|probe| !rules.contains(*page, *probe))
+    })
 }
 
-fn fix(arr: &mut [ResultType], rules: &[(ResultType, ResultType)]) {
+fn fix(arr: &mut [ResultType], rules: &Rules) {
     arr.sort_unstable_by(|a, b| {
-        if rules.contains(&(*b, *a)) {
+        if rules.contains(*b, *a) {
             Ordering::Greater
-        } else {
+        } else if rules.contains(*a, *b) {
             Ordering::Less
+        } else {
+            Ordering::Equal
         }
     });
 }
 
 enum Step {
     Ordering(ResultType, ResultType),
-    Update(ArrayVec<ResultType, 32>),
+    Update(List<ResultType, MAX_PAGES>),
 }
 
 fn ordering(input: &[u8]) -> IResult<&[u8], Step> {
-    map_res(
-        tuple((integer, tag(b"|"), integer, newline)),
-        |(lhs, _, rhs, _)| Ok::<_, &[u8]>(Step::Ordering(lhs, rhs)),
-    )(input)
-}
-
-fn list_number<RT, const C: usize>(input: &[u8]) -> IResult<&[u8], ArrayVec<RT, C>>
-where
-    RT: core::convert::TryFrom<i32>
-        + core::convert::TryFrom<u8>
-        + core::ops::MulAssign<RT>
-        + core::ops::AddAssign
-        + core::default::Default,
-    <RT as core::convert::TryFrom<u8>>::Error: core::fmt::Debug,
-    <RT as core::convert::TryFrom<i32>>::Error: core::fmt::Debug,
-{
-    fold_many1(
-        tuple((integer::<RT>, tag(b","))),
-        ArrayVec::new,
-        |mut acc: ArrayVec<RT, C>, item| {
-            acc.push(item.0);
-            acc
-        },
-    )(input)
+    map(rule, |(lhs, rhs)| Step::Ordering(lhs, rhs))(input)
 }
 
 fn update(input: &[u8]) -> IResult<&[u8], Step> {
-    map_res(tuple((list_number, integer, newline)), |(mut u, v, _)| {
-        u.push(v);
-        Ok::<_, &[u8]>(Step::Update(u))
-    })(input)
+    map(numbers(b",", "day5 pages"), Step::Update)(input)
 }
 fn parse(input: &[u8]) -> IResult<&[u8], Step> {
     alt((ordering, update))(input)
@@ -1,84 +1,149 @@
 use crate::{debug, info};
 use arrayvec::ArrayVec;
 
-use nom::combinator::iterator;
 use nom::combinator::map_res;
 use nom::IResult;
-use static_cell::StaticCell;
 
-use super::utils::direction::Direction;
+use super::utils::parse::items;
 use super::utils::parse::newline;
 use super::utils::parse::non_newline;
+use super::utils::status::{self, Phase};
+use super::utils::{Answers, Bounded, CapacityExceeded, DayError, DayResult};
 
-const FULL: &[u8] = include_bytes!("../../input/day6.full");
-const SAMPLE: &[u8] = include_bytes!("../../input/day6.sample");
+const MAX_GRID: usize = super::config::day6::GRID;
+const NUM_CELLS: usize = MAX_GRID * MAX_GRID;
+const MAX_TURNS: usize = super::config::day6::TURNS;
 
-static SEEN: StaticCell<[u8; 40_000]> = StaticCell::new();
-static SEEN_WITH_OBSTACLE: StaticCell<[u8; 40_000]> = StaticCell::new();
+/// Directions as indices into the jump tables, in the order the guard turns.
+const N: usize = 0;
+const E: usize = 1;
+const S: usize = 2;
+const W: usize = 3;
+/// A jump that leaves the grid.
+const EXIT: u8 = u8::MAX;
+/// In a cell, the low bits hold 1 + the direction the guard first entered it
+/// in; the high four, the directions it has turned to in it, one per bit.
+const FIRST_ENTERED: u8 = 0x0F;
+const TURNED: u8 = 0x10;
+const _: () = assert!(MAX_GRID < EXIT as usize, "jumps are u8 coordinates");
 
-pub struct Solution {
-    seen: &'static mut [u8; 40_000],
-    seen_with_obstacle: &'static mut [u8; 40_000],
+/// The guard only ever changes course at an obstacle, so both parts move it
+/// from one obstacle to the next with jump tables rather than a step at a
+/// time, and part 2 checks for a loop by the turns it makes alone.
+pub struct Solution<'s> {
+    cells: &'s mut [u8],
+    jumps: [&'s mut [u8]; 4],
 }
-impl super::utils::Solution for Solution {
-    fn new() -> impl super::utils::Solution {
-        let seen = SEEN.init_with(|| [0; 40_000]);
-        let seen_with_obstacle = SEEN_WITH_OBSTACLE.init_with(|| [0; 40_000]);
-        Self {
-            seen,
-            seen_with_obstacle,
-        }
+impl super::utils::Solution for Solution<'_> {
+    fn new(
+        scratch: &super::utils::Scratch,
+    ) -> Result<impl super::utils::Solution, super::utils::CapacityExceeded> {
+        let table = || scratch.slice_within(NUM_CELLS, || 0, "day6 scratch");
+        Ok(Solution {
+            cells: table()?,
+            jumps: [table()?, table()?, table()?, table()?],
+        })
     }
 
-    fn run_sample(&mut self) {
-        run("sample", SAMPLE, self.seen, self.seen_with_obstacle)
+    fn run_sample(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("sample", input, self, answers)
     }
 
-    fn run_full(&mut self) {
-        run("full", FULL, self.seen, self.seen_with_obstacle)
+    fn run_full(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("full", input, self, answers)
     }
 }
 
 fn run(
     label: &'static str,
     data: &[u8],
-    seen: &mut [u8; 40_000],
-    seen_with_obstacle: &mut [u8; 40_000],
-) {
+    solution: &mut Solution,
+    answers: &mut Answers,
+) -> DayResult {
     info!("{} start parsing", label);
-    let mut grid = ArrayVec::<&[u8], 200>::new();
-    let mut it = iterator(data, grid_line);
+    status::set(Phase::Parsing);
+    let mut grid = ArrayVec::<&[u8], MAX_GRID>::new();
+    let mut it = items(data, grid_line);
     for line in &mut it {
-        grid.push(line);
+        if line.len() > MAX_GRID {
+            return Err(CapacityExceeded::new("day6 grid columns", MAX_GRID).into());
+        }
+        grid.push_within(line, "day6 grid rows")?;
         if line.is_empty() {
             info!("Manual abort");
             break;
         }
     }
+    it.finish()?;
     info!("{} read {} gridlines", label, grid.len());
     info!("{} start processing", label);
-    seen.fill(0);
-    analyse(&grid, seen, None);
-    let part1_answer = seen.iter().filter(|v| **v != 0).count();
+    status::set(Phase::Solving);
+    let Some(start) = guard_startpos(&grid) else {
+        return Err(DayError::Invalid("cannot find the guard in the grid"));
+    };
+    let jumps = Jumps::new(&grid, &mut solution.jumps);
+    let cells = &mut *solution.cells;
+    cells.fill(0);
+
+    // Walk the route, noting how the guard first enters each cell on it, and
+    // where it turns, in case it never leaves
+    let (mut at, mut direction) = (start, N);
+    loop {
+        let stop = jumps.next(at, direction, None);
+        let end = stop.unwrap_or_else(|| jumps.edge(at, direction));
+        for cell in jumps.line(at, end, direction) {
+            let first = &mut cells[index(cell)];
+            if *first == 0 {
+                *first = direction as u8 + 1;
+            }
+        }
+        let Some(stop) = stop else {
+            break;
+        };
+        (at, direction) = (stop, turn(direction));
+        let cell = &mut cells[index(at)];
+        if *cell & (TURNED << direction) != 0 {
+            return Err(DayError::Invalid("the guard never leaves"));
+        }
+        *cell |= TURNED << direction;
+    }
+    cells.iter_mut().for_each(|cell| *cell &= FIRST_ENTERED);
+    let part1_answer = cells.iter().filter(|v| **v != 0).count();
     info!("{} part1 answer = {}", label, part1_answer);
+    answers.record(1, part1_answer);
 
+    // An obstacle only changes the route from where the guard first reaches
+    // it, so each is tried from the cell before, facing it
     let mut part2_answer = 0;
-    for y in 0..200 {
-        for x in 0..200 {
-            let idx = x + y * 200;
-            match seen.get(idx) {
-                Some(i) if *i != 0 => {
-                    seen_with_obstacle.fill(0);
-                    if analyse(&grid, seen_with_obstacle, Some((x as isize, y as isize))) {
-                        part2_answer += 1;
-                        debug!("{} loop @ {},{}", label, x, y);
-                    }
-                }
-                _ => {}
-            }
+    let mut turns = ArrayVec::<u16, MAX_TURNS>::new();
+    for (x, y) in (0..grid.len()).flat_map(|y| (0..MAX_GRID).map(move |x| (x, y))) {
+        let first = cells[index((x, y))] & FIRST_ENTERED;
+        if first == 0 || (x, y) == start {
+            continue;
+        }
+        status::checkpoint();
+        let direction = first as usize - 1;
+        let from = step_back((x, y), direction);
+        let looped = loops(&jumps, cells, &mut turns, from, direction, (x, y));
+        for turned in turns.drain(..) {
+            cells[turned as usize] &= FIRST_ENTERED;
+        }
+        if looped? {
+            part2_answer += 1;
+            debug!("{} loop @ {},{}", label, x, y);
         }
     }
     info!("{} part2 answer = {}", label, part2_answer);
+    answers.record(2, part2_answer);
+    Ok(())
 }
 
 fn grid_line(input: &[u8]) -> IResult<&[u8], &[u8]> {
@@ -87,100 +152,161 @@ fn grid_line(input: &[u8]) -> IResult<&[u8], &[u8]> {
     })(input)
 }
 
-fn analyse<const C: usize, const N: usize>(
-    grid: &ArrayVec<&[u8], C>,
-    has_seen: &mut [u8; N],
-    obstacle: Option<(isize, isize)>,
-) -> bool {
-    let (mut guard_x, mut guard_y, mut direction) = guard_startpos(grid);
-    if matches!(obstacle, Some((x, y)) if x == guard_x && y == guard_y) {
-        return false;
+/// Walks the guard from `at`, with an extra obstacle, turn to turn, noting
+/// each turn in `cells` and `turns` until one repeats or the guard leaves.
+fn loops(
+    jumps: &Jumps,
+    cells: &mut [u8],
+    turns: &mut ArrayVec<u16, MAX_TURNS>,
+    mut at: (usize, usize),
+    mut direction: usize,
+    obstacle: (usize, usize),
+) -> Result<bool, CapacityExceeded> {
+    while let Some(stop) = jumps.next(at, direction, Some(obstacle)) {
+        (at, direction) = (stop, turn(direction));
+        let cell = &mut cells[index(at)];
+        if *cell & (TURNED << direction) != 0 {
+            return Ok(true);
+        }
+        *cell |= TURNED << direction;
+        turns.push_within(index(at) as u16, "day6 turns")?;
     }
-    loop {
-        debug!(
-            "guard: {}, {}, seen {} positions",
-            guard_x,
-            guard_y,
-            has_seen.len()
-        );
-        let d = match direction {
-            Direction::N => 1,
-            Direction::E => 2,
-            Direction::S => 4,
-            Direction::W => 8,
-            _ => unreachable!(),
-        };
-        let idx = guard_x + guard_y * 200;
-        match has_seen.get_mut(idx as usize) {
-            Some(v) => {
-                if *v & d != 0 {
-                    return true;
+    Ok(false)
+}
+
+/// For each direction and cell, how far along the row or column the guard
+/// gets before the next obstacle, as the x or y it stops at, or `EXIT`.
+struct Jumps<'a> {
+    tables: &'a [&'a mut [u8]; 4],
+    width: usize,
+    height: usize,
+}
+
+impl<'a> Jumps<'a> {
+    fn new<const C: usize>(grid: &ArrayVec<&[u8], C>, tables: &'a mut [&mut [u8]; 4]) -> Self {
+        let height = grid.len();
+        let width = grid.first().map(|r| r.len()).unwrap_or_default();
+        let blocked = |x: usize, y: usize| grid[y].get(x) == Some(&b'#');
+        for x in 0..width {
+            let mut stop = EXIT;
+            for y in 0..height {
+                tables[N][index((x, y))] = stop;
+                if blocked(x, y) {
+                    stop = y as u8 + 1;
                 }
-                *v |= d;
             }
-            None => {
-                has_seen[idx as usize] = d;
-                //has_seen.insert((guard_x, guard_y), d);
+            // Heading back, an obstacle at 0 leaves nowhere to stop; wrapping
+            // is harmless, as no cell is left to be given it
+            stop = EXIT;
+            for y in (0..height).rev() {
+                tables[S][index((x, y))] = stop;
+                if blocked(x, y) {
+                    stop = (y as u8).wrapping_sub(1);
+                }
             }
-        };
-        let (dx, dy, rotated_direction) = match direction {
-            Direction::N => (0, -1, Direction::E),
-            Direction::E => (1, 0, Direction::S),
-            Direction::S => (0, 1, Direction::W),
-            Direction::W => (-1, 0, Direction::N),
-            _ => unreachable!(),
-        };
-        // let decision = match get(grid, guard_x + dx, guard_y + dy) {
-        //     _ if obstacle
-        //         .map(|(ox, oy)| guard_x + dx == ox && guard_y + dy == oy)
-        //         .unwrap_or(false) =>
-        //     {
-        //         Decision::Turn
-        //     }
-        //     Some(b'.') | Some(b'^') => Decision::Step,
-        //     Some(_) => Decision::Turn,
-        //     None => Decision::Exit,
-        // };
-        // (guard_x, guard_y, direction) = match decision {
-        //     Decision::Step => (guard_x + dx, guard_y + dy, direction),
-        //     Decision::Turn => (guard_x, guard_y, rotated_direction),
-        //     Decision::Exit => {
-        //         // Doesn't exist - left grid, so didn't loop
-        //         return false;
-        //     }
-        // }
-        (guard_x, guard_y, direction) = match get(grid, guard_x + dx, guard_y + dy) {
-            _ if obstacle
-                .map(|(ox, oy)| guard_x + dx == ox && guard_y + dy == oy)
-                .unwrap_or(false) =>
-            {
-                (guard_x, guard_y, rotated_direction)
+        }
+        for y in 0..height {
+            let mut stop = EXIT;
+            for x in 0..width {
+                tables[W][index((x, y))] = stop;
+                if blocked(x, y) {
+                    stop = x as u8 + 1;
+                }
             }
-            Some(b'.') | Some(b'^') => (guard_x + dx, guard_y + dy, direction),
-            Some(_) => (guard_x, guard_y, rotated_direction),
-            None => {
-                // Doesn't exist - left grid, so didn't loop
-                return false;
+            stop = EXIT;
+            for x in (0..width).rev() {
+                tables[E][index((x, y))] = stop;
+                if blocked(x, y) {
+                    stop = (x as u8).wrapping_sub(1);
+                }
             }
         }
+        Jumps {
+            tables,
+            width,
+            height,
+        }
     }
-}
 
-fn guard_startpos<const C: usize>(grid: &ArrayVec<&[u8], C>) -> (isize, isize, Direction) {
-    for (y, r) in grid.iter().enumerate() {
-        for (x, c) in r.iter().enumerate() {
-            if c == &b'^' {
-                return (x as isize, y as isize, Direction::N);
+    /// Where the guard stops, heading `direction` from `at`, given an extra
+    /// `obstacle`; `None` if it leaves the grid.
+    fn next(
+        &self,
+        (x, y): (usize, usize),
+        direction: usize,
+        obstacle: Option<(usize, usize)>,
+    ) -> Option<(usize, usize)> {
+        let stop = self.tables[direction][index((x, y))];
+        let stop = (stop != EXIT).then_some(stop as usize);
+        // The extra obstacle counts if it is ahead, and nearer than the stop
+        let (along, ahead) = match (direction, obstacle) {
+            (N, Some((ox, oy))) if ox == x && oy < y => (y, Some(oy + 1)),
+            (S, Some((ox, oy))) if ox == x && oy > y => (y, Some(oy - 1)),
+            (W, Some((ox, oy))) if oy == y && ox < x => (x, Some(ox + 1)),
+            (E, Some((ox, oy))) if oy == y && ox > x => (x, Some(ox - 1)),
+            (N | S, _) => (y, None),
+            _ => (x, None),
+        };
+        let stop = match (stop, ahead) {
+            (Some(stop), Some(ahead)) if ahead.abs_diff(along) < stop.abs_diff(along) => {
+                Some(ahead)
             }
+            (None, ahead) => ahead,
+            (stop, _) => stop,
+        }?;
+        Some(match direction {
+            N | S => (x, stop),
+            _ => (stop, y),
+        })
+    }
+
+    /// The last cell before the edge, heading `direction` from `at`.
+    fn edge(&self, (x, y): (usize, usize), direction: usize) -> (usize, usize) {
+        match direction {
+            N => (x, 0),
+            S => (x, self.height - 1),
+            W => (0, y),
+            _ => (self.width - 1, y),
         }
     }
-    panic!("Cannot find the guard in the grid");
+
+    /// The cells from `from` to `to`, both included, heading `direction`.
+    fn line(
+        &self,
+        from: (usize, usize),
+        to: (usize, usize),
+        direction: usize,
+    ) -> impl Iterator<Item = (usize, usize)> {
+        let steps = from.0.abs_diff(to.0) + from.1.abs_diff(to.1);
+        (0..=steps).map(move |step| match direction {
+            N => (from.0, from.1 - step),
+            S => (from.0, from.1 + step),
+            W => (from.0 - step, from.1),
+            _ => (from.0 + step, from.1),
+        })
+    }
+}
+
+fn turn(direction: usize) -> usize {
+    (direction + 1) % 4
 }
 
-fn get<'a, const C: usize>(grid: &'a ArrayVec<&[u8], C>, x: isize, y: isize) -> Option<&'a u8> {
-    if x < 0 || y < 0 {
-        None
-    } else {
-        grid.get(y as usize).and_then(|r| r.get(x as usize))
+/// The cell the guard entered `at` from, heading `direction`.
+fn step_back((x, y): (usize, usize), direction: usize) -> (usize, usize) {
+    match direction {
+        N => (x, y + 1),
+        S => (x, y - 1),
+        W => (x + 1, y),
+        _ => (x - 1, y),
     }
 }
+
+fn index((x, y): (usize, usize)) -> usize {
+    x + y * MAX_GRID
+}
+
+fn guard_startpos<const C: usize>(grid: &ArrayVec<&[u8], C>) -> Option<(usize, usize)> {
+    grid.iter()
+        .enumerate()
+        .find_map(|(y, r)| r.iter().position(|c| *c == b'^').map(|x| (x, y)))
+}
@@ -1,77 +1,104 @@
-use crate::{debug, error, info};
+use crate::{debug, info};
 use arrayvec::ArrayVec;
 
-use nom::bytes::complete::tag;
-use nom::combinator::map_res;
 use nom::IResult;
 
 use super::utils::parse::integer;
-use super::utils::parse::list_number;
-use super::utils::parse::newline;
+use super::utils::parse::key_value;
+use super::utils::parse::numbers;
+use super::utils::parse::{LineReader, List, Source};
+use super::utils::status::{self, Phase};
+use super::utils::{Answers, DayResult};
 
 type ResultType = u64;
 
-const FULL: &[u8] = include_bytes!("../../input/day7.full");
-const SAMPLE: &[u8] = include_bytes!("../../input/day7.sample");
+const MAX_LINE: usize = super::config::day7::LINE;
+const MAX_VALUES: usize = super::config::day7::VALUES;
 
 pub struct Solution {}
 impl super::utils::Solution for Solution {
-    fn new() -> impl super::utils::Solution {
-        Self {}
+    fn new(
+        _scratch: &super::utils::Scratch,
+    ) -> Result<impl super::utils::Solution, super::utils::CapacityExceeded> {
+        Ok(Self {})
     }
 
-    fn run_sample(&mut self) {
-        run("sample", SAMPLE)
+    fn run_sample(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("sample", input, answers)
     }
 
-    fn run_full(&mut self) {
-        run("full", FULL)
+    fn run_full(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("full", input, answers)
+    }
+
+    fn run_stream(
+        &mut self,
+        label: &'static str,
+        source: &mut dyn Source,
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run(label, source, answers)
     }
 }
 
-fn run(label: &'static str, data: &[u8]) {
+fn run(label: &'static str, source: impl Source, answers: &mut Answers) -> DayResult {
     info!("{} start parsing", label);
-    let mut data = data;
-    let mut row = 0;
+    status::set(Phase::Parsing);
     let mut total1 = 0;
     let mut total2 = 0;
-    loop {
-        if let Ok((r, (answer, values))) = parse(data) {
-            debug!("{}: {} -> {} values", row, answer, values.len());
-            if can_be_true(&answer, values.as_ref(), false) {
-                total1 += answer;
-            }
-            if can_be_true(&answer, values.as_ref(), true) {
-                total2 += answer;
-            }
-            data = r;
-        } else {
-            error!("{}: Error ({})", row, data[0]);
-            break;
+    let mut lines = LineReader::<_, MAX_LINE>::new(source);
+    let mut row = 0;
+    while let Some(line) = lines.parse_line(parse) {
+        let (answer, values) = line?;
+        let values = values?;
+        row += 1;
+        status::checkpoint();
+        debug!("{}: {} -> {} values", row, answer, values.len());
+        if can_be_true(&answer, values.as_ref(), false) {
+            total1 += answer;
         }
-        if data.is_empty() {
-            break;
+        if can_be_true(&answer, values.as_ref(), true) {
+            total2 += answer;
         }
-        row += 1;
     }
     info!("{} part1 answer: {}", label, total1);
+    answers.record(1, total1);
     info!("{} part2 answer: {}", label, total2);
+    answers.record(2, total2);
+    Ok(())
+}
+
+fn parse(input: &[u8]) -> IResult<&[u8], (ResultType, List<ResultType, MAX_VALUES>)> {
+    key_value(integer, b": ", numbers(b" ", "day7 values"))(input)
 }
 
-fn parse(input: &[u8]) -> IResult<&[u8], (ResultType, ArrayVec<ResultType, 50>)> {
-    map_res(
-        nom::sequence::tuple((integer, tag(":"), list_number, newline)),
-        |(answer, _, params, _)| Ok::<_, &[u8]>((answer, params)),
-    )(input)
+/// What goes between two values, evaluated strictly left to right.
+#[derive(Debug, Clone, Copy)]
+enum Operator {
+    Multiply,
+    Add,
+    /// Part 2 only: appends the digits of the right value to the left
+    Concatenate,
 }
 
 fn can_be_true(answer: &ResultType, values: &[ResultType], is_part2: bool) -> bool {
     let mut operators = values
         .iter()
-        .map(|_| '*')
+        .map(|_| Operator::Multiply)
         .skip(1)
-        .collect::<ArrayVec<_, 50>>();
+        .collect::<ArrayVec<_, MAX_VALUES>>();
     let operators = operators.as_mut();
+    if operators.is_empty() {
+        return values[0] == *answer;
+    }
     test_all_up_to(operators.len() - 1, answer, values, operators, is_part2)
 }
 
@@ -79,51 +106,36 @@ fn test_all_up_to(
     n: usize,
     answer: &ResultType,
     values: &[ResultType],
-    operators: &mut [char],
+    operators: &mut [Operator],
     is_part2: bool,
 ) -> bool {
-    operators[n] = '*';
-    if n > 0 {
-        if test_all_up_to(n - 1, answer, values, operators, is_part2) {
-            return true;
-        }
-    } else if evaluate(values, operators, is_part2) == *answer {
-        return true;
-    }
-    operators[n] = '+';
-    if n > 0 {
-        if test_all_up_to(n - 1, answer, values, operators, is_part2) {
-            return true;
-        }
-    } else if evaluate(values, operators, is_part2) == *answer {
-        return true;
-    }
-    if is_part2 {
-        operators[n] = '|';
+    let choices: &[Operator] = if is_part2 {
+        &[Operator::Multiply, Operator::Add, Operator::Concatenate]
+    } else {
+        &[Operator::Multiply, Operator::Add]
+    };
+    for operator in choices {
+        operators[n] = *operator;
         if n > 0 {
             if test_all_up_to(n - 1, answer, values, operators, is_part2) {
                 return true;
             }
-        } else if evaluate(values, operators, is_part2) == *answer {
+        } else if evaluate(values, operators) == *answer {
             return true;
         }
     }
     false
 }
 
-fn evaluate(values: &[ResultType], operators: &[char], is_part2: bool) -> ResultType {
+fn evaluate(values: &[ResultType], operators: &[Operator]) -> ResultType {
     let mut answer = values[0];
-    for p in 0..operators.len() {
-        let operator = operators[p];
-        let rhs = values[p + 1];
+    for (operator, rhs) in operators.iter().zip(&values[1..]) {
         answer = match operator {
-            '*' => answer * rhs,
-            '+' => answer + rhs,
-            '|' if is_part2 => concatenate(answer, rhs),
-            _ => panic!(),
+            Operator::Multiply => answer * rhs,
+            Operator::Add => answer + rhs,
+            Operator::Concatenate => concatenate(answer, *rhs),
         }
     }
-    //info!(?answer);
     answer
 }
 
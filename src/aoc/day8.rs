@@ -1,57 +1,95 @@
-use crate::{error, info};
+use crate::info;
 use arrayvec::ArrayVec;
 
 use nom::combinator::map_res;
-use nom::combinator::{iterator, opt};
+use nom::combinator::opt;
 use nom::IResult;
-use scapegoat::SgSet;
 
+use super::utils::parse::items;
 use super::utils::parse::newline;
 use super::utils::parse::non_newline;
+use super::utils::status::{self, Phase};
+use super::utils::{Answers, Bounded, CapacityExceeded, DayResult};
 
-const FULL: &[u8] = include_bytes!("../../input/day8.full");
-const SAMPLE: &[u8] = include_bytes!("../../input/day8.sample");
+const MAX_GRID: usize = super::config::day8::GRID;
+const MAX_ANTENNAS: usize = super::config::day8::ANTENNAS;
+const _: () = assert!(MAX_GRID <= u8::MAX as usize, "antennas are u8 coordinates");
 
-pub struct Solution {}
-impl super::utils::Solution for Solution {
-    fn new() -> impl super::utils::Solution {
-        Self {}
+/// Antennas only make antinodes with others of their frequency, so they are
+/// grouped by it and only those pairs tried; antinodes are marked in a bit
+/// per cell.
+pub struct Solution<'s> {
+    antinodes: &'s mut [u32],
+}
+impl super::utils::Solution for Solution<'_> {
+    fn new(
+        scratch: &super::utils::Scratch,
+    ) -> Result<impl super::utils::Solution, super::utils::CapacityExceeded> {
+        let antinodes =
+            scratch.slice_within((MAX_GRID * MAX_GRID).div_ceil(32), || 0, "day8 scratch")?;
+        Ok(Solution { antinodes })
     }
 
-    fn run_sample(&mut self) {
-        run("sample", SAMPLE)
+    fn run_sample(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("sample", input, self.antinodes, answers)
     }
 
-    fn run_full(&mut self) {
-        run("full", FULL)
+    fn run_full(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("full", input, self.antinodes, answers)
     }
 }
 
-fn run(label: &'static str, data: &[u8]) {
+/// An antenna: its frequency, then where it is.
+type Antenna = (u8, u8, u8);
+
+fn run(label: &'static str, data: &[u8], bits: &mut [u32], answers: &mut Answers) -> DayResult {
     info!("{} start parsing", label);
-    let mut grid = ArrayVec::<&[u8], 200>::new();
-    let mut it = iterator(data, grid_line);
+    status::set(Phase::Parsing);
+    let mut antennas = ArrayVec::<Antenna, MAX_ANTENNAS>::new();
+    let mut width = 0;
+    let mut height = 0;
+    let mut it = items(data, grid_line);
     for line in &mut it {
-        grid.push(line);
         if line.is_empty() {
             info!("Manual abort");
             break;
         }
-    }
-    match it.finish() {
-        Ok((r, _)) => {
-            if !r.is_empty() {
-                panic!("{} residual size: {} of {}", label, r.len(), data.len())
+        if height == MAX_GRID {
+            return Err(CapacityExceeded::new("day8 grid rows", MAX_GRID).into());
+        }
+        if line.len() > MAX_GRID {
+            return Err(CapacityExceeded::new("day8 grid columns", MAX_GRID).into());
+        }
+        for (x, c) in line.iter().enumerate() {
+            if *c != b'.' {
+                antennas.push_within((*c, x as u8, height as u8), "day8 antennas")?;
             }
         }
-        Err(_e) => error!("{} error", label),
+        width = width.max(line.len());
+        height += 1;
     }
-    info!("{} read {} rows", label, grid.len());
+    it.finish()?;
+    info!("{} read {} rows", label, height);
     info!("{} start processing", label);
-    let step1_answer = analyse_part1(&grid);
+    status::set(Phase::Solving);
+    antennas.sort_unstable();
+    let mut antinodes = Antinodes::new(bits, width, height);
+    let step1_answer = analyse_part1(&antennas, &mut antinodes);
     info!("{} part1 answer: {}", label, step1_answer);
-    let step2_answer = analyse_part2(&grid);
+    answers.record(1, step1_answer);
+    let mut antinodes = Antinodes::new(antinodes.bits, width, height);
+    let step2_answer = analyse_part2(&antennas, &mut antinodes);
     info!("{} part2 answer: {}", label, step2_answer);
+    answers.record(2, step2_answer);
+    Ok(())
 }
 
 fn grid_line(input: &[u8]) -> IResult<&[u8], &[u8]> {
@@ -61,109 +99,77 @@ fn grid_line(input: &[u8]) -> IResult<&[u8], &[u8]> {
     )(input)
 }
 
-fn analyse_part1<const C: usize>(grid: &ArrayVec<&[u8], C>) -> usize {
-    let mut antinodes = SgSet::<(isize, isize), 1024>::new();
-    let min_y = 0_isize;
-    let max_y = (grid.len() - 1) as isize;
-    let min_x = 0_isize;
-    let max_x = (grid.first().unwrap().len() - 1) as isize;
+/// Each pair of antennas of a frequency, `antennas` being sorted by it.
+fn pairs(antennas: &[Antenna]) -> impl Iterator<Item = ((isize, isize), (isize, isize))> + '_ {
+    antennas
+        .chunk_by(|a, b| a.0 == b.0)
+        .flat_map(|group| {
+            group
+                .iter()
+                .enumerate()
+                .flat_map(move |(i, a)| group[i + 1..].iter().map(move |b| (*a, *b)))
+        })
+        .map(|((_, ax, ay), (_, bx, by))| ((ax as isize, ay as isize), (bx as isize, by as isize)))
+}
 
-    for (p1_y, p1_r) in grid.iter().enumerate() {
-        for (p1_x, p1_c) in p1_r.iter().enumerate() {
-            for (p2_y, p2_r) in grid.iter().enumerate() {
-                for (p2_x, p2_c) in p2_r.iter().enumerate() {
-                    let p1x = p1_x as isize;
-                    let p1y = p1_y as isize;
-                    let p2x = p2_x as isize;
-                    let p2y = p2_y as isize;
+fn analyse_part1(antennas: &[Antenna], antinodes: &mut Antinodes) -> usize {
+    for ((ax, ay), (bx, by)) in pairs(antennas) {
+        let (dx, dy) = (bx - ax, by - ay);
+        antinodes.insert(ax - dx, ay - dy);
+        antinodes.insert(bx + dx, by + dy);
+    }
+    antinodes.count
+}
 
-                    if p1x == p2x && p1y == p2y {
-                        continue;
-                    }
-                    if p1_c != p2_c {
-                        continue;
-                    }
-                    if *p1_c == b'.' {
-                        continue;
-                    }
-                    let dx = p1x - p2x;
-                    let dy = p1y - p2y;
-                    for (x, y) in [
-                        (p1x + dx, p1y + dy),
-                        (p2x + dx, p2y + dy),
-                        (p1x - dx, p1y - dy),
-                        (p2x - dx, p2y - dy),
-                    ] {
-                        if x != p1x
-                            && y != p1y
-                            && x != p2x
-                            && y != p2y
-                            && x >= min_x
-                            && x <= max_x
-                            && y >= min_y
-                            && y <= max_y
-                        {
-                            antinodes.insert((x, y));
-                        }
-                    }
-                }
-            }
+fn analyse_part2(antennas: &[Antenna], antinodes: &mut Antinodes) -> usize {
+    for ((ax, ay), (bx, by)) in pairs(antennas) {
+        let (dx, dy) = (bx - ax, by - ay);
+        let (mut x, mut y) = (ax, ay);
+        while antinodes.insert(x, y) {
+            x -= dx;
+            y -= dy;
+        }
+        let (mut x, mut y) = (bx, by);
+        while antinodes.insert(x, y) {
+            x += dx;
+            y += dy;
         }
     }
-    antinodes.len()
+    antinodes.count
 }
 
-fn analyse_part2<const C: usize>(grid: &ArrayVec<&[u8], C>) -> usize {
-    let mut antinodes = SgSet::<(isize, isize), 1024>::new();
-    let min_y = 0_isize;
-    let max_y = (grid.len() - 1) as isize;
-    let min_x = 0_isize;
-    let max_x = (grid.first().unwrap().len() - 1) as isize;
-
-    for (p1_y, p1_r) in grid.iter().enumerate() {
-        for (p1_x, p1_c) in p1_r.iter().enumerate() {
-            for (p2_y, p2_r) in grid.iter().enumerate() {
-                for (p2_x, p2_c) in p2_r.iter().enumerate() {
-                    let p1x = p1_x as isize;
-                    let p1y = p1_y as isize;
-                    let p2x = p2_x as isize;
-                    let p2y = p2_y as isize;
+/// The cells of the grid holding an antinode, a bit each.
+struct Antinodes<'b> {
+    bits: &'b mut [u32],
+    width: usize,
+    height: usize,
+    count: usize,
+}
 
-                    if p1x == p2x && p1y == p2y {
-                        continue;
-                    }
-                    if p1_c != p2_c {
-                        continue;
-                    }
-                    if *p1_c == b'.' {
-                        continue;
-                    }
-                    let dx = p1x - p2x;
-                    let dy = p1y - p2y;
+impl<'b> Antinodes<'b> {
+    fn new(bits: &'b mut [u32], width: usize, height: usize) -> Self {
+        bits.fill(0);
+        Antinodes {
+            bits,
+            width,
+            height,
+            count: 0,
+        }
+    }
 
-                    let mut x = p1x;
-                    let mut y = p1y;
-                    loop {
-                        if x < min_x || x > max_x || y < min_y || y > max_y {
-                            break;
-                        }
-                        antinodes.insert((x, y));
-                        x -= dx;
-                        y -= dy;
-                    }
-                    let mut x = p1x;
-                    let mut y = p1y;
-                    loop {
-                        if x < min_x || x > max_x || y < min_y || y > max_y {
-                            break;
-                        }
-                        antinodes.insert((x, y));
-                        x += dx;
-                        y += dy;
-                    }
-                }
-            }
+    /// Marks an antinode at `(x, y)`, returning whether that is on the grid.
+    fn insert(&mut self, x: isize, y: isize) -> bool {
+        let (Ok(x), Ok(y)) = (usize::try_from(x), usize::try_from(y)) else {
+            return false;
+        };
+        if x >= self.width || y >= self.height {
+            return false;
+        }
+        let bit = y * MAX_GRID + x;
+        if self.bits[bit / 32] & (1 << (bit % 32)) == 0 {
+            self.bits[bit / 32] |= 1 << (bit % 32);
+            self.count += 1;
         }
+        true
     }
-    antinodes.len()
 }
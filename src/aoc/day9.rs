@@ -1,170 +1,211 @@
-use arrayvec::ArrayVec;
-use static_cell::StaticCell;
-
-use nonmax::NonMaxU16;
-
+use nom::character::complete::digit1;
+use nom::combinator::map_res;
+use nom::combinator::opt;
+use nom::sequence::tuple;
+use nom::IResult;
+
+use super::utils::parse::{items, newline};
+use super::utils::status::{self, Phase};
+use super::utils::{Answers, CapacityExceeded, DayResult};
 use crate::{debug, info};
 
 type ResultType = u64;
 
-const NUM_BLOCKS: usize = 95000;
-
-const FULL: &[u8] = include_bytes!("../../input/day9.full");
-const SAMPLE: &[u8] = include_bytes!("../../input/day9.sample");
-static BLOCKS: StaticCell<ArrayVec<Block, NUM_BLOCKS>> = StaticCell::new();
+const MAX_GAPS: usize = super::config::day9::GAPS;
 
-pub struct Solution {
-    blocks: &'static mut ArrayVec<Block, NUM_BLOCKS>,
+/// The map is already a list of spans, file and free in turn, so both parts
+/// work from it directly, never laying the disk out block by block. Part 2
+/// keeps only the space left in each free span.
+pub struct Solution<'s> {
+    free: &'s mut [u8],
 }
-impl super::utils::Solution for Solution {
-    fn new() -> impl super::utils::Solution {
-        let blocks = BLOCKS.init_with(|| ArrayVec::new());
-        Self { blocks }
+impl super::utils::Solution for Solution<'_> {
+    fn new(
+        scratch: &super::utils::Scratch,
+    ) -> Result<impl super::utils::Solution, super::utils::CapacityExceeded> {
+        let free = scratch.slice_within(MAX_GAPS, || 0, "day9 scratch")?;
+        Ok(Solution { free })
     }
 
-    fn run_sample(&mut self) {
-        run("sample", SAMPLE, self.blocks)
+    fn run_sample(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("sample", input, self.free, answers)
     }
 
-    fn run_full(&mut self) {
-        run("full", FULL, self.blocks)
+    fn run_full(
+        &mut self,
+        input: &[u8],
+        answers: &mut super::utils::Answers,
+    ) -> super::utils::DayResult {
+        run("full", input, self.free, answers)
     }
 }
 
-fn run(label: &'static str, data: &[u8], blocks: &mut ArrayVec<Block, NUM_BLOCKS>) {
+fn run(label: &'static str, data: &[u8], free: &mut [u8], answers: &mut Answers) -> DayResult {
+    info!("{} start parsing", label);
+    status::set(Phase::Parsing);
+    let mut it = items(data, disk_map);
+    let map = DiskMap::new(it.next().unwrap_or_default());
+    it.finish()?;
     info!("{} start processing", label);
-    let step1_answer = analyse_part1(data, blocks);
-    info!("{} blocks count: {}", label, blocks.len());
+    status::set(Phase::Solving);
+    info!("{} files count: {}", label, map.files());
+    let step1_answer = analyse_part1(&map);
     info!("{} part1 answer: {}", label, step1_answer);
-    let step2_answer = analyse_part2(data, blocks);
+    answers.record(1, step1_answer);
+    let step2_answer = analyse_part2(&map, free)?;
     info!("{} part2 answer: {}", label, step2_answer);
+    answers.record(2, step2_answer);
+    Ok(())
 }
 
-fn analyse_part1(data: &[u8], blocks: &mut ArrayVec<Block, NUM_BLOCKS>) -> ResultType {
-    diskmap(data, blocks);
-    let mut s = 0;
-    let mut e = blocks.len() - 1;
-
-    loop {
-        while blocks[s] != Block::Empty {
-            s += 1;
+/// Moves blocks one at a time from the end of the disk into the first free
+/// block, a file's blocks at the back and the free space at the front being
+/// used up in step.
+fn analyse_part1(map: &DiskMap) -> ResultType {
+    let mut sum = 0;
+    let mut at = 0;
+    let Some(mut back) = map.files().checked_sub(1) else {
+        return 0;
+    };
+    let mut left = map.file(back);
+    let mut id = 0;
+    while id < back {
+        sum += checksum(id, at, map.file(id));
+        at += map.file(id);
+        let mut free = map.gap(id);
+        while free > 0 {
+            let moved = free.min(left);
+            sum += checksum(back, at, moved);
+            at += moved;
+            free -= moved;
+            left -= moved;
+            if left == 0 {
+                back -= 1;
+                left = map.file(back);
+                if back == id {
+                    break;
+                }
+            }
         }
-        while blocks[e] == Block::Empty {
-            e -= 1;
+        id += 1;
+    }
+    if id == back {
+        sum += checksum(back, at, left);
+    }
+    sum
+}
+
+/// Moves each file, last first, into the leftmost free span that it fits,
+/// if one comes before it.
+///
+/// Free spans only ever shrink, so the leftmost that fits a file of a given
+/// size only ever moves right: a [`Cursor`] per size finds it, passing each
+/// free span at most once over the whole disk.
+fn analyse_part2(map: &DiskMap, free: &mut [u8]) -> Result<ResultType, CapacityExceeded> {
+    let gaps = map.files().saturating_sub(1);
+    if gaps > free.len() {
+        return Err(CapacityExceeded::new("day9 gaps", free.len()));
+    }
+    let free = &mut free[..gaps];
+    for (gap, left) in free.iter_mut().enumerate() {
+        *left = map.gap(gap) as u8;
+    }
+    let mut cursors = [Cursor::new(map); 10];
+
+    let mut sum = 0;
+    let mut at = map.blocks();
+    for id in (0..map.files()).rev() {
+        status::checkpoint();
+        let len = map.file(id);
+        at -= map.gap(id) + len;
+        if len == 0 {
+            continue;
         }
-        if s >= e {
-            break;
+        let cursor = &mut cursors[len];
+        while cursor.gap < id && (free[cursor.gap] as usize) < len {
+            cursor.next(map);
         }
-        blocks[s] = blocks[e];
-        blocks[e] = Block::Empty;
+        let start = if cursor.gap < id {
+            let left = &mut free[cursor.gap];
+            let start = cursor.start + map.gap(cursor.gap) - *left as usize;
+            *left -= len as u8;
+            debug!("file {} moves from {} to {}", id, at, start);
+            start
+        } else {
+            at
+        };
+        sum += checksum(id, start, len);
     }
 
-    checksum(blocks.as_ref())
+    Ok(sum)
 }
 
-fn analyse_part2(data: &[u8], blocks: &mut ArrayVec<Block, NUM_BLOCKS>) -> ResultType {
-    diskmap(data, blocks);
+/// Of file `id`, `len` blocks long from block `start`.
+fn checksum(id: usize, start: usize, len: usize) -> ResultType {
+    let (start, len) = (start as ResultType, len as ResultType);
+    id as ResultType * (start * len + len * len.saturating_sub(1) / 2)
+}
 
-    let mut e = (blocks.len() - 1) as isize;
-    let mut seen = u16::MAX;
+/// The single line of span lengths.
+fn disk_map(input: &[u8]) -> IResult<&[u8], &[u8]> {
+    map_res(tuple((digit1, opt(newline))), |(map, _)| {
+        Ok::<_, &[u8]>(map)
+    })(input)
+}
 
-    loop {
-        while e >= 0
-            && (
-                match blocks[e as usize] {
-                    Block::FileBlock(b) if b.get() < seen => false,
-                    _ => true,
-                }
-                // || handled.contains(&blocks[e as usize])
-            )
-        {
-            e -= 1;
-            debug!("e: {}, end: {}", e, (e >= 0));
-        }
-        if e < 0 {
-            break;
-        }
-        //handled.insert(blocks[e as usize]);
-        match blocks[e as usize] {
-            Block::FileBlock(b) => {
-                seen = b.get();
-            }
-            _ => panic!(),
-        };
-        let mut num_e = 0;
-        while (e - num_e) >= 0 && blocks[e as usize] == blocks[(e - num_e) as usize] {
-            num_e += 1;
-        }
-        debug!("block: {:?}, num_e: {}", blocks[e as usize], num_e);
-        let mut s = 0;
-        let (s, num_s, can_move) = loop {
-            while s < (e - num_e) && blocks[s as usize] != Block::Empty {
-                s += 1;
-            }
-            if s >= (e - num_e) || blocks[s as usize] != Block::Empty {
-                break (s, 0, false);
-            }
-            let mut num_s = 1;
-            while num_s < num_e
-                && ((s + num_s) as usize) < blocks.len()
-                && blocks[(s + num_s) as usize] == Block::Empty
-            {
-                num_s += 1;
-            }
-            if num_s >= num_e {
-                break (s, num_s, true);
-            }
-            s += num_s;
-        };
-        debug!(
-            "s: {}, num_s: {}, e: {}, can_move: {}",
-            s, num_s, e, can_move
-        );
-        if can_move {
-            for i in 0..num_e {
-                blocks[(s + i) as usize] = blocks[(e - num_e + 1 + i) as usize];
-                blocks[(e - num_e + 1 + i) as usize] = Block::Empty;
-            }
-        }
+/// The spans of the disk: file `id` is followed by free span `id`, but the
+/// last file by none.
+struct DiskMap<'d> {
+    map: &'d [u8],
+}
+
+impl<'d> DiskMap<'d> {
+    fn new(map: &'d [u8]) -> Self {
+        DiskMap { map }
     }
-    debug!("blocks: {:?}", blocks);
 
-    checksum(blocks.as_ref())
-}
+    fn files(&self) -> usize {
+        self.map.len().div_ceil(2)
+    }
 
-fn checksum(blocks: &[Block]) -> ResultType {
-    blocks
-        .iter()
-        .enumerate()
-        .map(|(pos, block)| {
-            if let Block::FileBlock(id) = block {
-                (id.get() as ResultType) * (pos as ResultType)
-            } else {
-                0
-            }
-        })
-        .sum()
+    fn file(&self, id: usize) -> usize {
+        self.len(2 * id)
+    }
+
+    fn gap(&self, id: usize) -> usize {
+        self.len(2 * id + 1)
+    }
+
+    fn blocks(&self) -> usize {
+        (0..self.map.len()).map(|span| self.len(span)).sum()
+    }
+
+    fn len(&self, span: usize) -> usize {
+        self.map.get(span).map_or(0, |c| (c - b'0') as usize)
+    }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
-enum Block {
-    #[default]
-    Empty,
-    FileBlock(NonMaxU16),
+/// A free span, and the block it started at before any file moved into it.
+#[derive(Clone, Copy)]
+struct Cursor {
+    gap: usize,
+    start: usize,
 }
 
-fn diskmap(map: &[u8], blocks: &mut ArrayVec<Block, NUM_BLOCKS>) {
-    blocks.clear();
-    for (id, c) in map.iter().enumerate() {
-        let c = *c - b'0';
-        let s = if id % 2 == 0 {
-            let id = NonMaxU16::new((id / 2) as u16).unwrap();
-            Block::FileBlock(id)
-        } else {
-            Block::Empty
-        };
-        for _ in 0..c {
-            blocks.push(s);
+impl Cursor {
+    fn new(map: &DiskMap) -> Self {
+        Cursor {
+            gap: 0,
+            start: map.file(0),
         }
     }
+
+    fn next(&mut self, map: &DiskMap) {
+        self.start += map.gap(self.gap) + map.file(self.gap + 1);
+        self.gap += 1;
+    }
 }
@@ -0,0 +1,32 @@
+//! Registry of the days that have inputs, generated by `build.rs`.
+//!
+//! * `DAYS` lists them in order and `SELECTED` is the one picked with
+//!   `AOC_DAY` at build time, if any, and `BENCH` the number of runs to
+//!   benchmark them with, picked with `AOC_BENCH`.
+//! * `run` solves one of them by number, with the inputs it embeds.
+//! * `ANSWERS` holds what `input/answers.toml` expects of each input.
+
+use super::task::{AocTask, Input};
+
+/// A day's built-in inputs, packed with the `compress-inputs` feature.
+pub struct Inputs {
+    pub sample: &'static [u8],
+    pub full: &'static [u8],
+}
+
+/// The answers expected of one input of a day, part 1 first.
+pub struct Answer {
+    pub day: u8,
+    /// `"sample"` or `"full"`
+    pub input: &'static str,
+    pub parts: &'static [&'static str],
+}
+
+include!(concat!(env!("OUT_DIR"), "/days.rs"));
+
+/// What `input/answers.toml` expects of `day` given the `input` label.
+pub fn expected(day: u8, input: &str) -> Option<&'static Answer> {
+    ANSWERS
+        .iter()
+        .find(|answer| answer.day == day && answer.input == input)
+}
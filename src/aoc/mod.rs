@@ -1,15 +1,58 @@
+mod bench;
+mod config;
+#[cfg(input = "day1")]
 mod day1;
+#[cfg(input = "day10")]
 mod day10;
+#[cfg(input = "day11")]
+mod day11;
+#[cfg(input = "day12")]
+mod day12;
+#[cfg(input = "day13")]
+mod day13;
+#[cfg(input = "day14")]
+mod day14;
+#[cfg(input = "day15")]
+mod day15;
+#[cfg(input = "day17")]
+mod day17;
+#[cfg(input = "day18")]
+mod day18;
+#[cfg(input = "day19")]
+mod day19;
+#[cfg(input = "day2")]
 mod day2;
+#[cfg(input = "day20")]
+mod day20;
+#[cfg(input = "day21")]
+mod day21;
+#[cfg(input = "day22")]
+mod day22;
+#[cfg(input = "day23")]
+mod day23;
+#[cfg(input = "day24")]
+mod day24;
+#[cfg(input = "day25")]
+mod day25;
+#[cfg(input = "day3")]
 mod day3;
+#[cfg(input = "day4")]
 mod day4;
+#[cfg(input = "day5")]
 mod day5;
+#[cfg(input = "day6")]
 mod day6;
+#[cfg(input = "day7")]
 mod day7;
+#[cfg(input = "day8")]
 mod day8;
+#[cfg(input = "day9")]
 mod day9;
+mod days;
+pub mod results;
 mod task;
 mod utils;
 
-pub use day9::Solution;
 pub use task::AocTask as Task;
+pub use utils::parse::Source;
+pub use utils::status;
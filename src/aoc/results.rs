@@ -0,0 +1,140 @@
+//! A record of each input `AocTask` runs, for logs that outlive the RTT
+//! session, such as the Pico's flash results log. Records encode to a fixed
+//! [`Record::SIZE`] bytes so that a log can store them in fixed slots.
+
+use core::fmt;
+
+use super::utils::{Answers, MAX_ANSWER};
+
+/// Where `AocTask` sends a [`Record`] once each input has run.
+pub trait ResultsLog {
+    fn append(&mut self, record: &Record);
+}
+
+/// Which of a day's inputs was run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum InputKind {
+    Sample,
+    Full,
+    Stream,
+}
+
+impl TryFrom<u8> for InputKind {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, ()> {
+        match value {
+            0 => Ok(InputKind::Sample),
+            1 => Ok(InputKind::Full),
+            2 => Ok(InputKind::Stream),
+            _ => Err(()),
+        }
+    }
+}
+
+/// How the run ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Outcome {
+    Solved,
+    /// A fixed-capacity container ran out of room part way
+    CapacityExceeded,
+    /// Overran the day's time budget, so the watchdog reset the board
+    TimedOut,
+    /// The input is not in the day's format
+    ParseFailed,
+    /// The input parsed, but has no answer
+    Invalid,
+    /// The day cannot take the input that way, such as streamed
+    Unsupported,
+}
+
+/// `AOC_BUILD_ID`, the short hash of the commit built, as a number.
+pub fn build_id() -> u32 {
+    u32::from_str_radix(env!("AOC_BUILD_ID"), 16).unwrap_or(0)
+}
+
+pub struct Record {
+    pub day: u8,
+    pub input: InputKind,
+    pub outcome: Outcome,
+    /// Time taken, parsing included
+    pub micros: u64,
+    /// `AOC_BUILD_ID` of the build that ran it
+    pub build: u32,
+    pub answers: Answers,
+}
+
+impl Record {
+    /// Day, input and outcome, 1 reserved byte, micros, build, then each
+    /// answer padded with NULs to `MAX_ANSWER` bytes.
+    pub const SIZE: usize = 16 + 2 * MAX_ANSWER;
+
+    pub fn encode(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0; Self::SIZE];
+        bytes[0] = self.day;
+        bytes[1] = self.input as u8;
+        bytes[2] = self.outcome as u8;
+        bytes[4..12].copy_from_slice(&self.micros.to_le_bytes());
+        bytes[12..16].copy_from_slice(&self.build.to_le_bytes());
+        for part in 1..=2 {
+            let answer = self.answers.part(part).as_bytes();
+            let start = 16 + (part as usize - 1) * MAX_ANSWER;
+            bytes[start..start + answer.len()].copy_from_slice(answer);
+        }
+        bytes
+    }
+
+    /// The record `bytes` encodes, if it is one.
+    pub fn decode(bytes: &[u8; Self::SIZE]) -> Option<Self> {
+        let input = InputKind::try_from(bytes[1]).ok()?;
+        let outcome = match bytes[2] {
+            0 => Outcome::Solved,
+            1 => Outcome::CapacityExceeded,
+            2 => Outcome::TimedOut,
+            3 => Outcome::ParseFailed,
+            4 => Outcome::Invalid,
+            5 => Outcome::Unsupported,
+            _ => return None,
+        };
+        let mut answers = Answers::default();
+        for part in 1..=2 {
+            let start = 16 + (part as usize - 1) * MAX_ANSWER;
+            let padded = &bytes[start..start + MAX_ANSWER];
+            let len = padded.iter().position(|b| *b == 0).unwrap_or(MAX_ANSWER);
+            answers.record(part, core::str::from_utf8(&padded[..len]).ok()?);
+        }
+        Some(Record {
+            day: bytes[0],
+            input,
+            outcome,
+            micros: u64::from_le_bytes(bytes[4..12].try_into().unwrap()),
+            build: u32::from_le_bytes(bytes[12..16].try_into().unwrap()),
+            answers,
+        })
+    }
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let input = match self.input {
+            InputKind::Sample => "sample",
+            InputKind::Full => "full",
+            InputKind::Stream => "stream",
+        };
+        write!(
+            f,
+            "build {:08x} day{} {} {}us",
+            self.build, self.day, input, self.micros
+        )?;
+        match self.outcome {
+            Outcome::Solved => write!(f, ": {} / {}", self.answers.part(1), self.answers.part(2)),
+            Outcome::CapacityExceeded => write!(f, ": capacity exceeded"),
+            Outcome::TimedOut => write!(f, ": timed out"),
+            Outcome::ParseFailed => write!(f, ": parse error"),
+            Outcome::Invalid => write!(f, ": invalid input"),
+            Outcome::Unsupported => write!(f, ": unsupported input"),
+        }
+    }
+}
@@ -1,15 +1,295 @@
-use super::Solution;
-use crate::aoc::utils::Solution as _;
+use core::mem::MaybeUninit;
 
-pub struct AocTask {}
+use static_cell::StaticCell;
 
-impl AocTask {
+use super::bench;
+use super::days::{self, Inputs};
+use super::results::{self, InputKind, Outcome, Record, ResultsLog};
+use crate::aoc::utils::parse::Source;
+use crate::aoc::utils::status::{self, Phase};
+use crate::aoc::utils::{clock, guard, Answers, DayError, DayResult, Scratch, Solution};
+#[cfg(feature = "compress-inputs")]
+use crate::aoc::utils::{unpack, CapacityExceeded};
+use crate::{error, info};
+
+/// Shared by every day; sized for the hungriest, day11's stone table.
+const SCRATCH_SIZE: usize = super::config::task::SCRATCH;
+
+static SCRATCH: StaticCell<[u8; SCRATCH_SIZE]> = StaticCell::new();
+
+pub struct AocTask<'a> {
+    scratch: Scratch,
+    log: Option<&'a mut dyn ResultsLog>,
+    skip: Option<u8>,
+}
+
+/// Where a day's input comes from.
+pub enum Input<'a> {
+    /// The sample and full inputs built into the binary
+    Embedded,
+    /// An input read piecewise, see [`AocTask::run_stream`]
+    Stream {
+        label: &'static str,
+        source: &'a mut dyn Source,
+    },
+    /// The full built-in input, run over and over to time it, as picked with
+    /// `AOC_BENCH` at build time
+    Bench { runs: usize },
+}
+
+impl<'a> AocTask<'a> {
     pub fn new() -> Self {
-        Self {}
+        let buffer: &'static mut MaybeUninit<_> = SCRATCH.uninit();
+        Self {
+            scratch: Scratch::new(buffer),
+            log: None,
+            skip: None,
+        }
+    }
+
+    /// Appends a [`Record`] of every input run from now on to `log`.
+    pub fn log_to(&mut self, log: &'a mut dyn ResultsLog) {
+        self.log = Some(log);
+    }
+
+    /// Leaves `day` out of [`run`](Self::run), such as one that timed out on
+    /// the last boot and would only do so again.
+    pub fn skip(&mut self, day: u8) {
+        self.skip = Some(day);
     }
+
+    /// Runs the day picked with `AOC_DAY` at build time, or every day that
+    /// has inputs; benchmarks them instead given `AOC_BENCH`.
     pub fn run(&mut self) {
-        let mut solution = Solution::new();
-        solution.run_sample();
-        solution.run_full();
+        match days::SELECTED {
+            Some(day) => {
+                if !self.run_day(day) {
+                    error!("day{} has no inputs", day);
+                }
+            }
+            None if days::DAYS.is_empty() => error!("no days have inputs"),
+            None => {
+                for day in days::DAYS {
+                    self.run_day(*day);
+                }
+            }
+        }
+    }
+
+    fn run_day(&mut self, day: u8) -> bool {
+        if self.skip == Some(day) {
+            info!("day{} skipped, having timed out", day);
+            return true;
+        }
+        let input = match days::BENCH {
+            Some(runs) => Input::Bench { runs },
+            None => Input::Embedded,
+        };
+        days::run(self, day, input)
+    }
+
+    /// Runs `day` on an input read piecewise from `source`, such as one
+    /// received over USB serial or TCP.
+    pub fn run_stream(&mut self, day: u8, label: &'static str, source: &mut dyn Source) {
+        if !days::run(self, day, Input::Stream { label, source }) {
+            error!("day{} has no inputs", day);
+        }
+    }
+
+    /// Called by the registry in `days` with the chosen day's `Solution`.
+    pub(super) fn run_solution<S: Solution>(&mut self, day: u8, inputs: &Inputs, input: Input) {
+        info!("day{} start", day);
+        // A benchmark gets the day's budget for every run
+        let budget_ms = match input {
+            Input::Bench { runs } => S::BUDGET_MS.saturating_mul(runs),
+            _ => S::BUDGET_MS,
+        };
+        let budget_ms = u32::try_from(budget_ms).unwrap_or(u32::MAX);
+        status::start_day(day, budget_ms);
+        guard::arm(budget_ms);
+        #[cfg(feature = "alloc")]
+        crate::heap::reset_peak();
+        {
+            // A day whose buffers do not fit the arena fails each input
+            // with that, like any other container that runs out of room
+            let mut solution = S::new(&self.scratch);
+            match input {
+                Input::Embedded => {
+                    let buffer = Self::input_buffer(&self.scratch, &[inputs.sample, inputs.full]);
+                    expect(day, "sample");
+                    if let Some(data) = Self::unpack(buffer, "sample", inputs.sample) {
+                        let input = (InputKind::Sample, "sample");
+                        solve(&mut self.log, day, input, |answers| {
+                            solution.as_mut().map_err(|e| *e)?.run_sample(data, answers)
+                        });
+                    }
+                    report_heap("sample");
+                    expect(day, "full");
+                    if let Some(data) = Self::unpack(buffer, "full", inputs.full) {
+                        let input = (InputKind::Full, "full");
+                        solve(&mut self.log, day, input, |answers| {
+                            solution.as_mut().map_err(|e| *e)?.run_full(data, answers)
+                        });
+                    }
+                    report_heap("full");
+                }
+                Input::Stream { label, source } => {
+                    solve(&mut self.log, day, (InputKind::Stream, label), |answers| {
+                        solution
+                            .as_mut()
+                            .map_err(|e| *e)?
+                            .run_stream(label, source, answers)
+                    });
+                    report_heap(label);
+                }
+                Input::Bench { runs } => {
+                    let buffer = Self::input_buffer(&self.scratch, &[inputs.full]);
+                    if let Some(data) = Self::unpack(buffer, "full", inputs.full) {
+                        status::start_input(InputKind::Full);
+                        let result = bench::run(day, runs, |answers| {
+                            solution.as_mut().map_err(|e| *e)?.run_full(data, answers)
+                        });
+                        report("full", result);
+                    }
+                    report_heap("full");
+                }
+            }
+        }
+        self.finish_day();
+    }
+
+    /// Built-in inputs are used in place, unless packed.
+    #[cfg(not(feature = "compress-inputs"))]
+    fn input_buffer<'s>(_scratch: &'s Scratch, _inputs: &[&'static [u8]]) -> &'s mut [u8] {
+        &mut []
+    }
+
+    /// Takes one buffer from the scratch arena, after the day's own buffers,
+    /// to unpack each of `inputs` into in turn, so the arena only needs room
+    /// for the largest of them. If that does not fit, takes what is left,
+    /// for any input small enough.
+    #[cfg(feature = "compress-inputs")]
+    fn input_buffer<'s>(scratch: &'s Scratch, inputs: &[&'static [u8]]) -> &'s mut [u8] {
+        let len = inputs
+            .iter()
+            .filter_map(|input| unpack::unpacked_len(input))
+            .max()
+            .unwrap_or(0);
+        let free = scratch.capacity() - scratch.used();
+        scratch.slice(len.min(free), || 0).unwrap_or_default()
+    }
+
+    #[cfg(not(feature = "compress-inputs"))]
+    fn unpack<'b>(
+        _buffer: &'b mut [u8],
+        _label: &'static str,
+        input: &'static [u8],
+    ) -> Option<&'b [u8]> {
+        Some(input)
+    }
+
+    /// Unpacks a built-in input into the buffer from
+    /// [`AocTask::input_buffer`], over any input unpacked there before.
+    #[cfg(feature = "compress-inputs")]
+    fn unpack<'b>(
+        buffer: &'b mut [u8],
+        label: &'static str,
+        input: &'static [u8],
+    ) -> Option<&'b [u8]> {
+        let Some(len) = unpack::unpacked_len(input) else {
+            error!("{} packed input is corrupt", label);
+            return None;
+        };
+        let capacity = buffer.len();
+        let Some(data) = buffer.get_mut(..len) else {
+            error!(
+                "{} {}",
+                label,
+                CapacityExceeded::new("unpacked input", capacity)
+            );
+            return None;
+        };
+        if unpack::unpack(input, data).is_none() {
+            error!("{} packed input is corrupt", label);
+            return None;
+        }
+        Some(data)
+    }
+
+    fn finish_day(&mut self) {
+        info!(
+            "scratch: {} of {} bytes used",
+            self.scratch.used(),
+            self.scratch.capacity()
+        );
+        self.scratch.reset();
+        guard::disarm();
+        status::set(Phase::Idle);
+    }
+}
+
+/// Logs what `input/answers.toml` expects of the input about to run, to
+/// compare with the answers the day logs.
+fn expect(day: u8, label: &'static str) {
+    let Some(answer) = days::expected(day, label) else {
+        return;
+    };
+    for (part, expected) in answer.parts.iter().enumerate() {
+        info!("{} part{} expected: {}", label, part + 1, expected);
+    }
+}
+
+/// Times one input's run, reports how it went and, given a log, appends a
+/// record of it.
+fn solve(
+    log: &mut Option<&mut dyn ResultsLog>,
+    day: u8,
+    (input, label): (InputKind, &'static str),
+    run: impl FnOnce(&mut Answers) -> DayResult,
+) {
+    let mut answers = Answers::default();
+    status::start_input(input);
+    let start = clock::now_micros();
+    let result = run(&mut answers);
+    let micros = clock::now_micros() - start;
+    if let Some(log) = log {
+        log.append(&Record {
+            day,
+            input,
+            outcome: match &result {
+                Ok(()) => Outcome::Solved,
+                Err(DayError::CapacityExceeded(_)) => Outcome::CapacityExceeded,
+                Err(DayError::Parse(_)) => Outcome::ParseFailed,
+                Err(DayError::Invalid(_)) => Outcome::Invalid,
+                Err(DayError::Unsupported(_)) => Outcome::Unsupported,
+            },
+            micros,
+            build: results::build_id(),
+            answers,
+        });
+    }
+    report(label, result);
+}
+
+/// A day that gives up on an input still leaves the next to run.
+fn report(label: &'static str, result: DayResult) {
+    if let Err(e) = result {
+        error!("{} {}", label, e);
     }
 }
+
+/// Logs the most heap in use at once while solving `label`, the day having
+/// already dropped its collections by now, and starts afresh for the next.
+#[cfg(feature = "alloc")]
+fn report_heap(label: &'static str) {
+    info!(
+        "{} heap: {} bytes at peak, {} bytes free",
+        label,
+        crate::heap::peak(),
+        crate::heap::free()
+    );
+    crate::heap::reset_peak();
+}
+
+#[cfg(not(feature = "alloc"))]
+fn report_heap(_label: &'static str) {}
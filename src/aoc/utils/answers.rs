@@ -0,0 +1,39 @@
+use core::fmt::{Display, Write};
+
+use arrayvec::ArrayString;
+
+use super::clock;
+
+/// Longest answer kept; longer ones are cut short.
+pub const MAX_ANSWER: usize = 48;
+
+/// A day's answers as text, collected as it logs them so `AocTask` can
+/// record them once the day is done, along with when each came in, for
+/// benchmarks.
+#[derive(Default)]
+pub struct Answers {
+    parts: [ArrayString<MAX_ANSWER>; 2],
+    /// [`clock::cycles`] as each part was recorded
+    at: [u64; 2],
+}
+
+impl Answers {
+    /// Records the answer to `part`, 1 or 2.
+    pub fn record(&mut self, part: u8, answer: impl Display) {
+        let slot = &mut self.parts[part as usize - 1];
+        slot.clear();
+        // A failed write keeps the pieces that fitted
+        let _ = write!(slot, "{}", answer);
+        self.at[part as usize - 1] = clock::cycles();
+    }
+
+    /// The answer to `part`, empty if there was none.
+    pub fn part(&self, part: u8) -> &str {
+        &self.parts[part as usize - 1]
+    }
+
+    /// When `part` was recorded, in [`clock::cycles`]; 0 if it was not.
+    pub fn recorded_at(&self, part: u8) -> u64 {
+        self.at[part as usize - 1]
+    }
+}
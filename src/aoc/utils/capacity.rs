@@ -0,0 +1,52 @@
+use core::fmt::Display;
+
+use arrayvec::ArrayVec;
+use scapegoat::SgSet;
+
+/// An input needed more room than one of a day's fixed-capacity containers
+/// has.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapacityExceeded {
+    /// Names the container, such as `"day1 left list"`
+    pub what: &'static str,
+    pub capacity: usize,
+}
+
+impl CapacityExceeded {
+    pub fn new(what: &'static str, capacity: usize) -> Self {
+        Self { what, capacity }
+    }
+}
+
+impl Display for CapacityExceeded {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "capacity exceeded: {} > {}", self.what, self.capacity)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for CapacityExceeded {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "capacity exceeded: {} > {}", self.what, self.capacity)
+    }
+}
+
+/// Adds to a fixed-capacity container, naming it in the error when full.
+pub trait Bounded<T> {
+    fn push_within(&mut self, value: T, what: &'static str) -> Result<(), CapacityExceeded>;
+}
+
+impl<T, const C: usize> Bounded<T> for ArrayVec<T, C> {
+    fn push_within(&mut self, value: T, what: &'static str) -> Result<(), CapacityExceeded> {
+        self.try_push(value)
+            .map_err(|_| CapacityExceeded::new(what, C))
+    }
+}
+
+impl<T: Ord + Default, const N: usize> Bounded<T> for SgSet<T, N> {
+    fn push_within(&mut self, value: T, what: &'static str) -> Result<(), CapacityExceeded> {
+        self.try_insert(value)
+            .map(|_| ())
+            .map_err(|_| CapacityExceeded::new(what, N))
+    }
+}
@@ -0,0 +1,54 @@
+//! A monotonic clock for timing runs: `embassy_time` on the Pico,
+//! `clock_gettime` on x86_64 Linux and the tick count on Windows.
+//!
+//! [`cycles`] is a finer one for benchmarking: the time stamp counter on x86
+//! hosts and, on the Pico, `embassy_time` scaled up to `clk_sys`, as the
+//! M0+ has no cycle counter and SysTick wraps every 24 bits.
+
+/// Microseconds since some fixed point, such as boot.
+#[cfg(all(target_arch = "arm", target_os = "none"))]
+pub fn now_micros() -> u64 {
+    embassy_time::Instant::now().as_micros()
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+pub fn now_micros() -> u64 {
+    // https://man7.org/linux/man-pages/man2/clock_gettime.2.html
+    const CLOCK_MONOTONIC: usize = 1;
+    let mut time = [0u64; 2];
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") 228,
+            in("rdi") CLOCK_MONOTONIC,
+            in("rsi") time.as_mut_ptr(),
+            lateout("rax") _,
+            out("rcx") _,
+            out("r11") _,
+        );
+    }
+    time[0] * 1_000_000 + time[1] / 1_000
+}
+
+#[cfg(target_os = "windows")]
+pub fn now_micros() -> u64 {
+    unsafe { windows_sys::Win32::System::SystemInformation::GetTickCount64() * 1_000 }
+}
+
+/// `clk_sys` as `embassy_rp::init` sets it up by default.
+#[cfg(all(target_arch = "arm", target_os = "none"))]
+const CLK_SYS_HZ: u64 = 125_000_000;
+
+/// CPU cycles since some fixed point, to within a microsecond's worth on the
+/// Pico.
+#[cfg(all(target_arch = "arm", target_os = "none"))]
+pub fn cycles() -> u64 {
+    embassy_time::Instant::now().as_ticks() * (CLK_SYS_HZ / embassy_time::TICK_HZ)
+}
+
+/// Time stamp counter ticks, which run at a fixed rate near the CPU's base
+/// clock on any recent x86.
+#[cfg(target_arch = "x86_64")]
+pub fn cycles() -> u64 {
+    unsafe { core::arch::x86_64::_rdtsc() }
+}
@@ -0,0 +1,56 @@
+use core::fmt::Display;
+
+use super::parse::{OwnedParseError, ParseError};
+use super::CapacityExceeded;
+
+/// Why a day gave up on an input. Returned from the `Solution` run methods
+/// and reported by `AocTask`, rather than panicking.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DayError {
+    /// A fixed-capacity container ran out of room
+    CapacityExceeded(CapacityExceeded),
+    /// The input is not in the day's format
+    Parse(OwnedParseError),
+    /// The input parsed, but has no answer, such as a maze with no way out
+    Invalid(&'static str),
+    /// The day cannot take input this way, such as streaming
+    Unsupported(&'static str),
+}
+
+impl From<CapacityExceeded> for DayError {
+    fn from(e: CapacityExceeded) -> Self {
+        DayError::CapacityExceeded(e)
+    }
+}
+
+impl From<ParseError<'_>> for DayError {
+    fn from(e: ParseError<'_>) -> Self {
+        DayError::Parse(e.into())
+    }
+}
+
+impl Display for DayError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DayError::CapacityExceeded(e) => e.fmt(f),
+            DayError::Parse(e) => e.fmt(f),
+            DayError::Invalid(why) => write!(f, "invalid input: {}", why),
+            DayError::Unsupported(what) => write!(f, "{} not supported", what),
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for DayError {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            DayError::CapacityExceeded(e) => e.format(f),
+            DayError::Parse(e) => e.format(f),
+            DayError::Invalid(why) => defmt::write!(f, "invalid input: {=str}", why),
+            DayError::Unsupported(what) => defmt::write!(f, "{=str} not supported", what),
+        }
+    }
+}
+
+/// Outcome of running one input through a day.
+pub type DayResult = Result<(), DayError>;
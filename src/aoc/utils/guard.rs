@@ -0,0 +1,111 @@
+//! Gives up on a day that overruns its time budget. On x86_64 Linux a
+//! `SIGALRM` timer is armed as each day starts; if it fires, the day is
+//! reported as timed out and the process exits. On the Pico the watchdog does
+//! this job, driven by [`status`](super::status) from its own task, so arming
+//! is a no-op there, as it is on Windows.
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+mod imp {
+    use core::arch::asm;
+    use core::fmt::Write;
+
+    use arrayvec::ArrayString;
+
+    use crate::aoc::utils::status;
+
+    const SIGALRM: usize = 14;
+    const SA_RESTORER: u64 = 0x0400_0000;
+    const ITIMER_REAL: usize = 0;
+
+    // https://man7.org/linux/man-pages/man2/sigaction.2.html, as the kernel
+    // lays it out rather than libc
+    #[repr(C)]
+    struct SigAction {
+        handler: extern "C" fn(i32),
+        flags: u64,
+        restorer: extern "C" fn(),
+        mask: u64,
+    }
+
+    /// Starts the running day's clock, `budget_ms` long.
+    pub fn arm(budget_ms: u32) {
+        let action = SigAction {
+            handler: timed_out,
+            flags: SA_RESTORER,
+            restorer: restore,
+            mask: 0,
+        };
+        // https://man7.org/linux/man-pages/man2/rt_sigaction.2.html
+        unsafe {
+            asm!(
+                "syscall",
+                in("rax") 13,
+                in("rdi") SIGALRM,
+                in("rsi") &action as *const SigAction,
+                in("rdx") 0,
+                in("r10") 8,
+                lateout("rax") _,
+                out("rcx") _,
+                out("r11") _,
+            );
+        }
+        set_timer(budget_ms);
+    }
+
+    /// Stops the clock, once the day is done.
+    pub fn disarm() {
+        set_timer(0);
+    }
+
+    // https://man7.org/linux/man-pages/man2/setitimer.2.html
+    fn set_timer(ms: u32) {
+        // No interval, so it fires once; then the value, as a timeval
+        let timer: [i64; 4] = [0, 0, (ms / 1000) as i64, (ms % 1000) as i64 * 1000];
+        unsafe {
+            asm!(
+                "syscall",
+                in("rax") 38,
+                in("rdi") ITIMER_REAL,
+                in("rsi") timer.as_ptr(),
+                in("rdx") 0,
+                lateout("rax") _,
+                out("rcx") _,
+                out("r11") _,
+            );
+        }
+    }
+
+    extern "C" fn timed_out(_signal: i32) {
+        let mut text = ArrayString::<32>::new();
+        let _ = writeln!(text, "day{} timed out", status::day());
+        unsafe {
+            asm!(
+                "syscall",
+                in("rax") 1,
+                in("rdi") 2,
+                in("rsi") text.as_ptr(),
+                in("rdx") text.len(),
+                lateout("rax") _,
+                out("rcx") _,
+                out("r11") _,
+            );
+            // exit_group, so every thread goes
+            asm!("syscall", in("rax") 231, in("rdi") 1, options(noreturn));
+        }
+    }
+
+    /// The kernel insists on a restorer, though `timed_out` never returns to
+    /// it.
+    extern "C" fn restore() {
+        unsafe { asm!("syscall", in("rax") 15, options(noreturn)) }
+    }
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+pub use imp::{arm, disarm};
+
+#[cfg(not(all(target_os = "linux", target_arch = "x86_64")))]
+pub fn arm(_budget_ms: u32) {}
+
+#[cfg(not(all(target_os = "linux", target_arch = "x86_64")))]
+pub fn disarm() {}
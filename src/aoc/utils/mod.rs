@@ -1,5 +1,18 @@
+mod answers;
+mod capacity;
+pub mod clock;
 pub mod direction;
+mod error;
+pub mod guard;
 pub mod parse;
+mod scratch;
 mod solution;
+pub mod status;
+#[cfg(feature = "compress-inputs")]
+pub mod unpack;
 
+pub use answers::{Answers, MAX_ANSWER};
+pub use capacity::{Bounded, CapacityExceeded};
+pub use error::{DayError, DayResult};
+pub use scratch::Scratch;
 pub use solution::Solution;
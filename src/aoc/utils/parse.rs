@@ -1,46 +1,306 @@
+use core::fmt::Display;
+
 use arrayvec::ArrayVec;
 use nom::bytes::complete::tag;
 use nom::bytes::complete::take_while1;
-use nom::character::complete::digit1;
-use nom::combinator::map_res;
-use nom::multi::fold_many1;
+use nom::character::complete::{digit1, one_of};
+use nom::combinator::{map, map_opt, opt, ParserIterator};
+use nom::error::ErrorKind;
+use nom::sequence::{separated_pair, tuple};
 use nom::IResult;
 
-pub fn integer<RT>(input: &[u8]) -> IResult<&[u8], RT>
-where
-    RT: core::convert::TryFrom<i32>
-        + core::convert::TryFrom<u8>
-        + core::ops::MulAssign<RT>
-        + core::ops::AddAssign,
-    <RT as core::convert::TryFrom<u8>>::Error: core::fmt::Debug,
-    <RT as core::convert::TryFrom<i32>>::Error: core::fmt::Debug,
-{
-    map_res(digit1, |digits: &[u8]| {
-        Ok::<RT, &[u8]>(digits.iter().fold(0.try_into().unwrap(), |mut acc, v| {
-            acc *= 10.try_into().unwrap();
-            acc += (v - b'0').try_into().unwrap();
-            acc
-        }))
-    })(input)
-}
-
-pub fn list_number<RT, const C: usize>(input: &[u8]) -> IResult<&[u8], ArrayVec<RT, C>>
-where
-    RT: core::convert::TryFrom<i32>
-        + core::convert::TryFrom<u8>
-        + core::ops::MulAssign<RT>
-        + core::ops::AddAssign
-        + core::default::Default,
-    <RT as core::convert::TryFrom<u8>>::Error: core::fmt::Debug,
-    <RT as core::convert::TryFrom<i32>>::Error: core::fmt::Debug,
-{
-    fold_many1(
-        nom::sequence::tuple((tag(" "), integer::<RT>)),
-        ArrayVec::new,
-        |mut acc: ArrayVec<RT, C>, item| {
-            acc.push(item.1);
-            acc
-        },
+use super::{Bounded, CapacityExceeded};
+
+/// Longest slice of the offending line kept in a [`ParseError`].
+const EXCERPT: usize = 40;
+
+/// Where, and on what, parsing of a day's input gave up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseError<'a> {
+    pub offset: usize,
+    /// 1-based
+    pub line: usize,
+    /// 1-based, in bytes
+    pub column: usize,
+    /// What the failing parser was looking for
+    pub expected: &'static str,
+    /// Part of the offending line, starting near the failure
+    pub excerpt: &'a [u8],
+}
+
+impl<'a> ParseError<'a> {
+    /// Locates `at`, a suffix of `data`, within it.
+    pub fn new(data: &'a [u8], at: &[u8], kind: ErrorKind) -> Self {
+        let offset = (at.as_ptr() as usize)
+            .saturating_sub(data.as_ptr() as usize)
+            .min(data.len());
+        let before = &data[..offset];
+        let line_start = before
+            .iter()
+            .rposition(|c| *c == b'\n')
+            .map_or(0, |pos| pos + 1);
+        let line_end = data[offset..]
+            .iter()
+            .position(|c| *c == b'\n' || *c == b'\r')
+            .map_or(data.len(), |pos| offset + pos);
+        let from = line_start.max(offset.saturating_sub(EXCERPT / 2));
+        Self {
+            offset,
+            line: before.iter().filter(|c| **c == b'\n').count() + 1,
+            column: offset - line_start + 1,
+            expected: expected(kind),
+            excerpt: &data[from..line_end.min(from + EXCERPT)],
+        }
+    }
+
+    /// Converts an error returned by parsing a suffix of `data`.
+    pub fn from_nom(data: &'a [u8], error: nom::Err<nom::error::Error<&[u8]>>) -> Self {
+        match error {
+            nom::Err::Error(e) | nom::Err::Failure(e) => Self::new(data, e.input, e.code),
+            nom::Err::Incomplete(_) => Self::new(data, &data[data.len()..], ErrorKind::Complete),
+        }
+    }
+
+    /// Moves an error found in a single line to where that line sits in
+    /// the whole input.
+    pub fn on_line(self, line: usize, offset: usize) -> Self {
+        Self {
+            offset: self.offset + offset,
+            line,
+            ..self
+        }
+    }
+
+    fn excerpt(&self) -> &'a str {
+        core::str::from_utf8(self.excerpt).unwrap_or("<not utf-8>")
+    }
+}
+
+impl Display for ParseError<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "parse error at line {} column {} (byte {}): expected {} in \"{}\"",
+            self.line,
+            self.column,
+            self.offset,
+            self.expected,
+            self.excerpt()
+        )
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for ParseError<'_> {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "parse error at line {} column {} (byte {}): expected {=str} in \"{=str}\"",
+            self.line,
+            self.column,
+            self.offset,
+            self.expected,
+            self.excerpt()
+        )
+    }
+}
+
+/// A [`ParseError`] with its own copy of the excerpt, so that it can be
+/// returned from a day after the input it points into has gone.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnedParseError {
+    offset: usize,
+    line: usize,
+    column: usize,
+    expected: &'static str,
+    excerpt: ArrayVec<u8, EXCERPT>,
+}
+
+impl OwnedParseError {
+    pub fn as_error(&self) -> ParseError<'_> {
+        ParseError {
+            offset: self.offset,
+            line: self.line,
+            column: self.column,
+            expected: self.expected,
+            excerpt: &self.excerpt,
+        }
+    }
+}
+
+impl From<ParseError<'_>> for OwnedParseError {
+    fn from(e: ParseError<'_>) -> Self {
+        Self {
+            offset: e.offset,
+            line: e.line,
+            column: e.column,
+            expected: e.expected,
+            excerpt: e.excerpt.iter().copied().take(EXCERPT).collect(),
+        }
+    }
+}
+
+impl Display for OwnedParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.as_error().fmt(f)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for OwnedParseError {
+    fn format(&self, fmt: defmt::Formatter) {
+        self.as_error().format(fmt)
+    }
+}
+
+fn expected(kind: ErrorKind) -> &'static str {
+    match kind {
+        ErrorKind::Digit => "digits",
+        ErrorKind::Tag | ErrorKind::Char => "literal",
+        ErrorKind::TakeWhile1 => "token",
+        ErrorKind::MapRes => "valid value",
+        ErrorKind::MapOpt => "number in range",
+        ErrorKind::Eof => "end of input",
+        ErrorKind::Complete => "more input",
+        ErrorKind::Alpha => "letters",
+        ErrorKind::AlphaNumeric => "letters or digits",
+        ErrorKind::Space | ErrorKind::MultiSpace => "whitespace",
+        ErrorKind::Alt => "one of several alternatives",
+        ErrorKind::Many1 | ErrorKind::Many1Count => "at least one item",
+        _ => "valid input",
+    }
+}
+
+/// Drives `nom::combinator::iterator` over `data`, keeping what is needed to
+/// explain where it stopped if that is not the end of the input.
+///
+/// Iterate with `for item in &mut it`, then call [`Items::finish`].
+pub fn items<'a, O, F>(data: &'a [u8], parser: F) -> Items<'a, F>
+where
+    F: FnMut(&'a [u8]) -> IResult<&'a [u8], O> + Clone,
+{
+    items_from(data, data, parser)
+}
+
+/// As [`items`], starting from `rest`, a suffix of `data` left over by
+/// parsing an earlier section of it.
+pub fn items_from<'a, O, F>(data: &'a [u8], rest: &'a [u8], parser: F) -> Items<'a, F>
+where
+    F: FnMut(&'a [u8]) -> IResult<&'a [u8], O> + Clone,
+{
+    Items {
+        data,
+        parser: parser.clone(),
+        it: nom::combinator::iterator(rest, parser),
+    }
+}
+
+pub struct Items<'a, F> {
+    data: &'a [u8],
+    parser: F,
+    it: ParserIterator<&'a [u8], nom::error::Error<&'a [u8]>, F>,
+}
+
+impl<'a, O, F> Iterator for Items<'a, F>
+where
+    F: FnMut(&'a [u8]) -> IResult<&'a [u8], O>,
+{
+    type Item = O;
+
+    fn next(&mut self) -> Option<O> {
+        (&mut self.it).next()
+    }
+}
+
+impl<'a, O, F> Items<'a, F>
+where
+    F: FnMut(&'a [u8]) -> IResult<&'a [u8], O>,
+{
+    /// Checks that every byte of the input was consumed.
+    pub fn finish(self) -> Result<(), ParseError<'a>> {
+        let Items {
+            data,
+            mut parser,
+            it,
+        } = self;
+        match it.finish() {
+            Ok(([], ())) => Ok(()),
+            // The iterator drops the error that stopped it, so provoke it again
+            Ok((rest, ())) => Err(match parser(rest) {
+                Err(e) => ParseError::from_nom(data, e),
+                // Matched without consuming anything
+                Ok(_) => ParseError::new(data, rest, ErrorKind::Eof),
+            }),
+            Err(e) => Err(ParseError::from_nom(data, e)),
+        }
+    }
+
+    /// Hands the input left after the last item to `parser`, for inputs made
+    /// of several sections. Errors are still located within the whole input.
+    pub fn then<G, P>(self, parser: G) -> Result<Items<'a, G>, ParseError<'a>>
+    where
+        G: FnMut(&'a [u8]) -> IResult<&'a [u8], P> + Clone,
+    {
+        let Items { data, it, .. } = self;
+        let (rest, ()) = it.finish().map_err(|e| ParseError::from_nom(data, e))?;
+        Ok(items_from(data, rest, parser))
+    }
+}
+
+/// Primitive integer types the number parsers can produce.
+///
+/// Digits are accumulated with checked arithmetic, so a number too large for
+/// `RT` is a parse error rather than a wrapped or panicking value.
+pub trait Integer: Copy {
+    const ZERO: Self;
+    /// `self * radix + digit`, or `None` on overflow
+    fn push_digit(self, radix: u8, digit: u8) -> Option<Self>;
+    /// `self * radix - digit`, or `None` on overflow. Negative numbers are
+    /// built downwards so that `MIN` itself can be parsed.
+    fn push_negative_digit(self, radix: u8, digit: u8) -> Option<Self>;
+}
+
+macro_rules! impl_integer {
+    ($($t:ty),*) => {
+        $(
+            impl Integer for $t {
+                const ZERO: Self = 0;
+
+                fn push_digit(self, radix: u8, digit: u8) -> Option<Self> {
+                    self.checked_mul(radix as $t)?.checked_add(digit as $t)
+                }
+
+                fn push_negative_digit(self, radix: u8, digit: u8) -> Option<Self> {
+                    self.checked_mul(radix as $t)?.checked_sub(digit as $t)
+                }
+            }
+        )*
+    };
+}
+
+impl_integer!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+fn accumulate<RT: Integer>(digits: &[u8], radix: u8, negative: bool) -> Option<RT> {
+    digits.iter().try_fold(RT::ZERO, |acc, c| {
+        let digit = (*c as char).to_digit(radix as u32)? as u8;
+        if negative {
+            acc.push_negative_digit(radix, digit)
+        } else {
+            acc.push_digit(radix, digit)
+        }
+    })
+}
+
+/// Unsigned decimal number.
+pub fn integer<RT: Integer>(input: &[u8]) -> IResult<&[u8], RT> {
+    map_opt(digit1, |digits: &[u8]| accumulate(digits, 10, false))(input)
+}
+
+/// Decimal number with an optional leading `-` or `+`.
+pub fn signed<RT: Integer>(input: &[u8]) -> IResult<&[u8], RT> {
+    map_opt(
+        tuple((opt(one_of("+-")), digit1)),
+        |(sign, digits): (Option<char>, &[u8])| accumulate(digits, 10, sign == Some('-')),
     )(input)
 }
 
@@ -55,3 +315,672 @@ pub fn non_newline(input: &[u8]) -> IResult<&[u8], &[u8]> {
 pub fn whitespace(input: &[u8]) -> IResult<&[u8], &[u8]> {
     take_while1(|c| c == b' ' || c == b'\t')(input)
 }
+
+/// Items parsed by [`separated`]: all of them, or which container they
+/// overflowed.
+pub type List<O, const C: usize> = Result<ArrayVec<O, C>, CapacityExceeded>;
+
+/// One or more of `item`, separated by `separator`, such as `75,47,61`.
+/// A trailing separator is left unconsumed. More than `C` items still parses,
+/// to the end of the list, but gives [`CapacityExceeded`] naming `what`.
+pub fn separated<'a, O, F, const C: usize>(
+    separator: &'static [u8],
+    what: &'static str,
+    mut item: F,
+) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], List<O, C>>
+where
+    F: FnMut(&'a [u8]) -> IResult<&'a [u8], O>,
+{
+    move |input: &'a [u8]| {
+        let mut list = Ok(ArrayVec::new());
+        let push = |list: &mut List<O, C>, next| {
+            if let Ok(items) = list {
+                if let Err(e) = items.push_within(next, what) {
+                    *list = Err(e);
+                }
+            }
+        };
+        let (mut input, first) = item(input)?;
+        push(&mut list, first);
+        loop {
+            let Ok((rest, _)) = tag::<_, _, nom::error::Error<_>>(separator)(input) else {
+                return Ok((input, list));
+            };
+            let Ok((rest, next)) = item(rest) else {
+                return Ok((input, list));
+            };
+            push(&mut list, next);
+            input = rest;
+        }
+    }
+}
+
+/// Unsigned decimal numbers separated by `separator`, such as `7 6 4 2 1`.
+pub fn numbers<'a, RT: Integer, const C: usize>(
+    separator: &'static [u8],
+    what: &'static str,
+) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], List<RT, C>> {
+    separated(separator, what, integer::<RT>)
+}
+
+/// `key`, then `separator`, then `value`, such as `190: 10 19`.
+pub fn key_value<'a, K, V, F, G>(
+    key: F,
+    separator: &'static [u8],
+    value: G,
+) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], (K, V)>
+where
+    F: FnMut(&'a [u8]) -> IResult<&'a [u8], K>,
+    G: FnMut(&'a [u8]) -> IResult<&'a [u8], V>,
+{
+    separated_pair(key, tag(separator), value)
+}
+
+/// Ordering rule `a|b`: `a` must come before `b`.
+pub fn rule<RT: Integer>(input: &[u8]) -> IResult<&[u8], (RT, RT)> {
+    key_value(integer, b"|", integer)(input)
+}
+
+/// Signed pair written with the given prefixes, such as `x=3,y=-4` with
+/// `b"x="` and `b"y="`, or `X+94, Y+34` with `b"X+"` and `b"Y+"`.
+pub fn coordinate<'a, RT: Integer>(
+    x: &'static [u8],
+    y: &'static [u8],
+) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], (RT, RT)> {
+    // A `+` in a prefix is left for `signed` to take as the sign
+    let x = x.strip_suffix(b"+").unwrap_or(x);
+    let y = y.strip_suffix(b"+").unwrap_or(y);
+    map(
+        tuple((tag(x), signed, tag(b","), opt(whitespace), tag(y), signed)),
+        |(_, x, _, _, _, y)| (x, y),
+    )
+}
+
+/// A block of non-blank lines, without its final line break, along with the
+/// blank lines ending it. Use with [`items`] to walk every section.
+pub fn section(input: &[u8]) -> IResult<&[u8], &[u8]> {
+    let blank = |line: &[u8]| line.iter().all(|c| c.is_ascii_whitespace());
+    let line_end = |from: usize| {
+        input[from..]
+            .iter()
+            .position(|c| *c == b'\n')
+            .map_or(input.len(), |pos| from + pos + 1)
+    };
+    let mut end = 0;
+    while end < input.len() && !blank(&input[end..line_end(end)]) {
+        end = line_end(end);
+    }
+    if end == 0 {
+        return Err(nom::Err::Error(nom::error::Error::new(
+            input,
+            ErrorKind::TakeWhile1,
+        )));
+    }
+    let mut rest = end;
+    while rest < input.len() && blank(&input[rest..line_end(rest)]) {
+        rest = line_end(rest);
+    }
+    let block = &input[..end];
+    let block = block.strip_suffix(b"\n").unwrap_or(block);
+    let block = block.strip_suffix(b"\r").unwrap_or(block);
+    Ok((&input[rest..], block))
+}
+
+/// Every match of `parser` in `data`, skipping whatever lies between them,
+/// for inputs where the wanted tokens are buried in noise.
+pub fn matches<'a, O, F>(data: &'a [u8], parser: F) -> Matches<'a, F>
+where
+    F: FnMut(&'a [u8]) -> IResult<&'a [u8], O>,
+{
+    Matches {
+        input: data,
+        parser,
+    }
+}
+
+pub struct Matches<'a, F> {
+    input: &'a [u8],
+    parser: F,
+}
+
+impl<'a, O, F> Iterator for Matches<'a, F>
+where
+    F: FnMut(&'a [u8]) -> IResult<&'a [u8], O>,
+{
+    type Item = O;
+
+    fn next(&mut self) -> Option<O> {
+        while !self.input.is_empty() {
+            match (self.parser)(self.input) {
+                Ok((rest, found)) if rest.len() < self.input.len() => {
+                    self.input = rest;
+                    return Some(found);
+                }
+                _ => self.input = &self.input[1..],
+            }
+        }
+        None
+    }
+}
+
+/// Input arriving piecewise, such as over USB serial, TCP or from an SD card.
+pub trait Source {
+    /// Fills the start of `buf`, returning how many bytes were read, or 0 at
+    /// the end of the input.
+    fn read(&mut self, buf: &mut [u8]) -> usize;
+}
+
+impl Source for &[u8] {
+    fn read(&mut self, buf: &mut [u8]) -> usize {
+        let len = buf.len().min(self.len());
+        buf[..len].copy_from_slice(&self[..len]);
+        *self = &self[len..];
+        len
+    }
+}
+
+impl<S: Source + ?Sized> Source for &mut S {
+    fn read(&mut self, buf: &mut [u8]) -> usize {
+        (**self).read(buf)
+    }
+}
+
+/// Splits a [`Source`] into lines, carrying any partial line over to the
+/// next read. Only `N` bytes are held at once, so `N` must exceed the
+/// longest line but not the whole input.
+pub struct LineReader<S, const N: usize> {
+    source: S,
+    buffer: [u8; N],
+    start: usize,
+    end: usize,
+    done: bool,
+    /// Lines returned so far
+    line: usize,
+    /// Bytes of the input before `buffer[start]`
+    offset: usize,
+    /// Bytes of the input before the latest line
+    line_offset: usize,
+}
+
+impl<S: Source, const N: usize> LineReader<S, N> {
+    pub fn new(source: S) -> Self {
+        Self {
+            source,
+            buffer: [0; N],
+            start: 0,
+            end: 0,
+            done: false,
+            line: 0,
+            offset: 0,
+            line_offset: 0,
+        }
+    }
+
+    /// The next line, without its line break, or `None` once the input is
+    /// exhausted.
+    pub fn next_line(&mut self) -> Option<Result<&[u8], ParseError<'_>>> {
+        Some(match self.advance()? {
+            Ok((from, to)) => Ok(&self.buffer[from..to]),
+            Err(()) => Err(self.too_long()),
+        })
+    }
+
+    /// Runs `parser` over the next line, which it must consume entirely.
+    /// Blank lines are skipped.
+    pub fn parse_line<'s, O, F>(&'s mut self, mut parser: F) -> Option<Result<O, ParseError<'s>>>
+    where
+        F: FnMut(&'s [u8]) -> IResult<&'s [u8], O>,
+    {
+        let (from, to) = loop {
+            match self.advance()? {
+                Ok((from, to)) if from == to => continue,
+                Ok(bounds) => break bounds,
+                Err(()) => return Some(Err(self.too_long())),
+            }
+        };
+        let (number, offset) = (self.line, self.line_offset);
+        let line = &self.buffer[from..to];
+        Some(match parser(line) {
+            Ok(([], found)) => Ok(found),
+            Ok((rest, _)) => {
+                Err(ParseError::new(line, rest, ErrorKind::Eof).on_line(number, offset))
+            }
+            Err(e) => Err(ParseError::from_nom(line, e).on_line(number, offset)),
+        })
+    }
+
+    /// Moves on to the next line, refilling the buffer as needed, and
+    /// returns where it lies in `buffer`; `Err` if it is longer than `N`.
+    fn advance(&mut self) -> Option<Result<(usize, usize), ()>> {
+        let (from, to) = loop {
+            let pending = &self.buffer[self.start..self.end];
+            if let Some(pos) = pending.iter().position(|c| *c == b'\n') {
+                break (self.start, self.start + pos + 1);
+            }
+            if self.done {
+                if pending.is_empty() {
+                    return None;
+                }
+                break (self.start, self.end);
+            }
+            self.buffer.copy_within(self.start..self.end, 0);
+            self.end -= self.start;
+            self.start = 0;
+            if self.end == N {
+                return Some(Err(()));
+            }
+            let read = self.source.read(&mut self.buffer[self.end..]);
+            self.end += read;
+            self.done = read == 0;
+        };
+        self.start = to;
+        self.line_offset = self.offset;
+        self.offset += to - from;
+        self.line += 1;
+        let line = &self.buffer[from..to];
+        let trimmed = line
+            .iter()
+            .rev()
+            .take_while(|c| **c == b'\n' || **c == b'\r')
+            .count();
+        Some(Ok((from, to - trimmed)))
+    }
+
+    fn too_long(&self) -> ParseError<'_> {
+        ParseError {
+            offset: self.offset + N,
+            line: self.line + 1,
+            column: N + 1,
+            expected: "line break",
+            excerpt: &self.buffer[..EXCERPT.min(N)],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::fmt::Write;
+
+    use arrayvec::ArrayString;
+    use nom::character::complete::alpha1;
+
+    use super::*;
+
+    fn map_opt_error(input: &[u8]) -> nom::Err<nom::error::Error<&[u8]>> {
+        nom::Err::Error(nom::error::Error::new(input, ErrorKind::MapOpt))
+    }
+
+    #[test]
+    fn integers() {
+        assert_eq!(integer::<u32>(b"190: 10"), Ok((&b": 10"[..], 190)));
+        assert_eq!(integer::<u8>(b"255"), Ok((&b""[..], u8::MAX)));
+        assert_eq!(integer::<u8>(b"007"), Ok((&b""[..], 7)));
+        assert!(integer::<u8>(b"").is_err());
+        assert!(integer::<u8>(b"+1").is_err());
+        assert!(integer::<u8>(b"-1").is_err());
+    }
+
+    #[test]
+    fn integer_overflow() {
+        assert_eq!(integer::<u8>(b"256"), Err(map_opt_error(b"256")));
+        assert_eq!(
+            integer::<u64>(b"18446744073709551616"),
+            Err(map_opt_error(b"18446744073709551616"))
+        );
+    }
+
+    #[test]
+    fn signed_numbers() {
+        assert_eq!(signed::<i32>(b"-4,"), Ok((&b","[..], -4)));
+        assert_eq!(signed::<i32>(b"+94"), Ok((&b""[..], 94)));
+        assert_eq!(signed::<i32>(b"34"), Ok((&b""[..], 34)));
+        assert_eq!(signed::<i32>(b"-0"), Ok((&b""[..], 0)));
+        assert_eq!(signed::<u8>(b"+255"), Ok((&b""[..], u8::MAX)));
+    }
+
+    #[test]
+    fn signed_limits() {
+        assert_eq!(
+            signed::<i64>(b"-9223372036854775808"),
+            Ok((&b""[..], i64::MIN))
+        );
+        assert_eq!(
+            signed::<i64>(b"9223372036854775807"),
+            Ok((&b""[..], i64::MAX))
+        );
+        assert!(signed::<i64>(b"-9223372036854775809").is_err());
+        assert!(signed::<i64>(b"9223372036854775808").is_err());
+        assert!(signed::<i8>(b"-129").is_err());
+        assert_eq!(signed::<u8>(b"-1"), Err(map_opt_error(b"-1")));
+    }
+
+    #[test]
+    fn bare_sign() {
+        assert!(signed::<i32>(b"-").is_err());
+        assert!(signed::<i32>(b"+").is_err());
+        assert!(signed::<i32>(b"-x").is_err());
+        assert!(signed::<i32>(b"--1").is_err());
+    }
+
+    /// An unsigned number on a line of its own.
+    fn line(input: &[u8]) -> IResult<&[u8], u8> {
+        map(tuple((integer, opt(newline))), |(value, _)| value)(input)
+    }
+
+    fn display(e: impl Display) -> ArrayString<128> {
+        let mut text = ArrayString::new();
+        write!(text, "{}", e).unwrap();
+        text
+    }
+
+    #[test]
+    fn error_location() {
+        let data = b"1,2\n3,x\n";
+        let e = ParseError::new(data, &data[6..], ErrorKind::Digit);
+        assert_eq!((e.offset, e.line, e.column), (6, 2, 3));
+        assert_eq!(e.expected, "digits");
+        assert_eq!(e.excerpt, b"3,x");
+        assert_eq!(
+            display(e).as_str(),
+            "parse error at line 2 column 3 (byte 6): expected digits in \"3,x\""
+        );
+    }
+
+    #[test]
+    fn error_at_end() {
+        let data = b"12\r\n";
+        let e = ParseError::from_nom(data, nom::Err::Incomplete(nom::Needed::Unknown));
+        assert_eq!((e.offset, e.line, e.column), (4, 2, 1));
+        assert_eq!(e.expected, "more input");
+        assert_eq!(e.excerpt, b"");
+    }
+
+    #[test]
+    fn excerpt_is_clipped() {
+        let mut data = [b'a'; 100];
+        data[60] = b'!';
+        let e = ParseError::new(&data, &data[60..], ErrorKind::Tag);
+        assert_eq!(e.column, 61);
+        assert_eq!(e.excerpt, &data[40..80]);
+
+        // Never reaches into the neighbouring lines, nor takes a `\r`
+        let data = b"abc\ndef\r\nghi";
+        let e = ParseError::new(data, &data[5..], ErrorKind::Tag);
+        assert_eq!((e.line, e.column), (2, 2));
+        assert_eq!(e.excerpt, b"def");
+    }
+
+    #[test]
+    fn error_on_line() {
+        let data = b"7 x";
+        let e = ParseError::new(data, &data[2..], ErrorKind::Digit).on_line(3, 10);
+        assert_eq!((e.offset, e.line, e.column), (12, 3, 3));
+    }
+
+    #[test]
+    fn owned_error_matches() {
+        let data = b"1\n2y\n";
+        let e = ParseError::new(data, &data[3..], ErrorKind::Tag);
+        let owned = OwnedParseError::from(e);
+        assert_eq!(owned.as_error(), e);
+        assert_eq!(display(&owned), display(e));
+    }
+
+    #[test]
+    fn items_consume_everything() {
+        let mut it = items(b"1\n2\n3", line);
+        assert_eq!((&mut it).map(u32::from).sum::<u32>(), 6);
+        assert_eq!(it.finish(), Ok(()));
+
+        let mut it = items(b"", line);
+        assert_eq!((&mut it).count(), 0);
+        assert_eq!(it.finish(), Ok(()));
+    }
+
+    #[test]
+    fn items_report_residue() {
+        let data = b"1\n2\nx3\n";
+        let mut it = items(data, line);
+        assert_eq!((&mut it).count(), 2);
+        let e = it.finish().unwrap_err();
+        assert_eq!((e.offset, e.line, e.column), (4, 3, 1));
+        assert_eq!(e.expected, "digits");
+        assert_eq!(e.excerpt, b"x3");
+
+        // Items left unread are residue too
+        let data = b"1\n2\n";
+        let mut it = items(data, line);
+        assert_eq!(it.next(), Some(1));
+        let e = it.finish().unwrap_err();
+        assert_eq!((e.line, e.column), (2, 1));
+        assert_eq!(e.expected, "end of input");
+    }
+
+    #[test]
+    fn items_then_next_section() {
+        let data = b"1|2\n3|4\n\n5\n6\n";
+        let mut rules = items(data, |input| {
+            map(tuple((rule::<u8>, newline)), |(rule, _)| rule)(input)
+        });
+        assert_eq!((&mut rules).count(), 2);
+        let mut values = rules.then(line).unwrap();
+        assert_eq!((&mut values).count(), 2);
+        assert_eq!(values.finish(), Ok(()));
+
+        // Errors in a later section still count lines from the start
+        let data = b"1|2\n\n5\n-6\n";
+        let mut rules = items(data, |input| {
+            map(tuple((rule::<u8>, newline)), |(rule, _)| rule)(input)
+        });
+        assert_eq!((&mut rules).count(), 1);
+        let mut values = rules.then(line).unwrap();
+        assert_eq!((&mut values).count(), 1);
+        let e = values.finish().unwrap_err();
+        assert_eq!((e.offset, e.line, e.column), (7, 4, 1));
+    }
+
+    #[test]
+    fn separated_lists() {
+        let (rest, list) = numbers::<u8, 4>(b",", "list")(b"75,47,61\n").unwrap();
+        assert_eq!(rest, b"\n");
+        assert_eq!(list.unwrap().as_slice(), &[75, 47, 61]);
+
+        let (rest, list) = separated::<_, _, 4>(b", ", "list", alpha1)(b"r, wr").unwrap();
+        assert_eq!(rest, b"");
+        assert_eq!(list.unwrap().as_slice(), &[&b"r"[..], &b"wr"[..]]);
+
+        assert!(numbers::<u8, 4>(b",", "list")(b"").is_err());
+        assert!(numbers::<u8, 4>(b",", "list")(b",1").is_err());
+    }
+
+    #[test]
+    fn separated_leaves_trailing_separator() {
+        let (rest, list) = numbers::<u8, 4>(b" ", "list")(b"7 6 ").unwrap();
+        assert_eq!(rest, b" ");
+        assert_eq!(list.unwrap().as_slice(), &[7, 6]);
+    }
+
+    #[test]
+    fn separated_overflow() {
+        let (rest, list) = numbers::<u8, 2>(b",", "day5 pages")(b"1,2,3,4\n").unwrap();
+        // The whole list is consumed, so only the overflow is reported
+        assert_eq!(rest, b"\n");
+        assert_eq!(list, Err(CapacityExceeded::new("day5 pages", 2)));
+
+        let (_, list) = numbers::<u8, 2>(b",", "day5 pages")(b"1,2").unwrap();
+        assert_eq!(list.unwrap().as_slice(), &[1, 2]);
+    }
+
+    #[test]
+    fn key_values() {
+        let (rest, (key, values)) =
+            key_value(integer::<u32>, b": ", numbers::<u32, 4>(b" ", "values"))(b"190: 10 19")
+                .unwrap();
+        assert_eq!(rest, b"");
+        assert_eq!(key, 190);
+        assert_eq!(values.unwrap().as_slice(), &[10, 19]);
+
+        assert!(key_value(integer::<u32>, b": ", integer::<u32>)(b"").is_err());
+        assert!(key_value(integer::<u32>, b": ", integer::<u32>)(b"190 10").is_err());
+        assert!(key_value(integer::<u32>, b": ", integer::<u32>)(b"190: ").is_err());
+    }
+
+    #[test]
+    fn rules() {
+        assert_eq!(rule::<u8>(b"47|53\n"), Ok((&b"\n"[..], (47, 53))));
+        assert!(rule::<u8>(b"").is_err());
+        assert!(rule::<u8>(b"47|").is_err());
+        assert!(rule::<u8>(b"47,53").is_err());
+        assert_eq!(rule::<u8>(b"47|256"), Err(map_opt_error(b"256")));
+    }
+
+    #[test]
+    fn coordinates() {
+        assert_eq!(
+            coordinate::<i32>(b"p=", b"")(b"p=0,4 v=3,-3"),
+            Ok((&b" v=3,-3"[..], (0, 4)))
+        );
+        assert_eq!(
+            coordinate::<i32>(b"x=", b"y=")(b"x=3, y=-4"),
+            Ok((&b""[..], (3, -4)))
+        );
+        assert_eq!(
+            coordinate::<u32>(b"X+", b"Y+")(b"X+94, Y+34\n"),
+            Ok((&b"\n"[..], (94, 34)))
+        );
+        assert!(coordinate::<i32>(b"x=", b"y=")(b"").is_err());
+        assert!(coordinate::<i32>(b"x=", b"y=")(b"x=3,").is_err());
+        assert!(coordinate::<i32>(b"x=", b"y=")(b"x=3;y=4").is_err());
+        assert!(coordinate::<i8>(b"x=", b"y=")(b"x=3,y=-129").is_err());
+    }
+
+    #[test]
+    fn sections() {
+        let data = b"a\nb\n\n \nc\r\n\r\n";
+        let (rest, first) = section(data).unwrap();
+        assert_eq!(first, b"a\nb");
+        assert_eq!(rest, b"c\r\n\r\n");
+        let (rest, second) = section(rest).unwrap();
+        assert_eq!(second, b"c");
+        assert_eq!(rest, b"");
+
+        assert_eq!(section(b"last"), Ok((&b""[..], &b"last"[..])));
+        assert!(section(b"").is_err());
+        assert!(section(b"\nafter a blank").is_err());
+    }
+
+    #[test]
+    fn all_matches() {
+        let found = matches(b"a1bb22c333", integer::<u16>).collect::<ArrayVec<_, 4>>();
+        assert_eq!(found.as_slice(), &[1, 22, 333]);
+
+        assert_eq!(matches(b"", integer::<u16>).count(), 0);
+        assert_eq!(matches(b"no digits", integer::<u16>).count(), 0);
+        // A parser that consumes nothing never matches
+        assert_eq!(matches(b"abc", opt(tag(b"x"))).count(), 0);
+    }
+
+    /// Hands out at most `chunk` bytes per read, as a slow link would.
+    struct Trickle {
+        data: &'static [u8],
+        chunk: usize,
+    }
+
+    impl Source for Trickle {
+        fn read(&mut self, buf: &mut [u8]) -> usize {
+            let len = buf.len().min(self.chunk);
+            self.data.read(&mut buf[..len])
+        }
+    }
+
+    fn read_lines<const N: usize>(
+        source: impl Source,
+    ) -> Result<ArrayVec<ArrayString<N>, 8>, OwnedParseError> {
+        let mut lines = LineReader::<_, N>::new(source);
+        let mut found = ArrayVec::new();
+        while let Some(line) = lines.next_line() {
+            let line = core::str::from_utf8(line?).unwrap();
+            found.push(ArrayString::from(line).unwrap());
+        }
+        Ok(found)
+    }
+
+    fn as_strs<const N: usize>(lines: &[ArrayString<N>]) -> ArrayVec<&str, 8> {
+        lines.iter().map(ArrayString::as_str).collect()
+    }
+
+    #[test]
+    fn lines_split_across_reads() {
+        let data = b"12 34\n56\r\n\n7 8 9\n";
+        let whole = read_lines::<8>(&data[..]).unwrap();
+        assert_eq!(as_strs(&whole).as_slice(), &["12 34", "56", "", "7 8 9"]);
+        for chunk in 1..=data.len() {
+            let source = Trickle { data, chunk };
+            assert_eq!(read_lines::<8>(source).unwrap(), whole, "chunk {}", chunk);
+        }
+    }
+
+    #[test]
+    fn last_line_without_break() {
+        for chunk in [1, 2, 100] {
+            let source = Trickle {
+                data: b"1\n22",
+                chunk,
+            };
+            assert_eq!(
+                as_strs(&read_lines::<4>(source).unwrap()).as_slice(),
+                &["1", "22"]
+            );
+        }
+        assert_eq!(read_lines::<4>(&b""[..]).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn line_filling_buffer() {
+        // The line break counts towards the line
+        let source = Trickle {
+            data: b"123\n456\n",
+            chunk: 2,
+        };
+        assert_eq!(
+            as_strs(&read_lines::<4>(source).unwrap()).as_slice(),
+            &["123", "456"]
+        );
+        // Without one, a line as long as the buffer cannot be told from a
+        // longer line until the input ends, so it is refused
+        let source = Trickle {
+            data: b"1\n1234",
+            chunk: 3,
+        };
+        assert_eq!(read_lines::<4>(source).unwrap_err().as_error().line, 2);
+    }
+
+    #[test]
+    fn line_longer_than_buffer() {
+        let source = Trickle {
+            data: b"12\n123456\n7\n",
+            chunk: 3,
+        };
+        let e = read_lines::<4>(source).unwrap_err();
+        let e = e.as_error();
+        assert_eq!((e.offset, e.line, e.column), (7, 2, 5));
+        assert_eq!(e.expected, "line break");
+        assert_eq!(e.excerpt, b"1234");
+    }
+
+    #[test]
+    fn parse_lines_across_reads() {
+        let source = Trickle {
+            data: b"1\n\n22\n33x\n",
+            chunk: 2,
+        };
+        let mut lines = LineReader::<_, 8>::new(source);
+        assert_eq!(lines.parse_line(integer::<u8>), Some(Ok(1)));
+        // The blank line is skipped
+        assert_eq!(lines.parse_line(integer::<u8>), Some(Ok(22)));
+        let e = lines.parse_line(integer::<u8>).unwrap().unwrap_err();
+        assert_eq!((e.offset, e.line, e.column), (8, 4, 3));
+        assert_eq!(e.expected, "end of input");
+        assert_eq!(e.excerpt, b"33x");
+    }
+}
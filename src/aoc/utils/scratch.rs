@@ -0,0 +1,72 @@
+use core::cell::Cell;
+use core::mem::{align_of, size_of, MaybeUninit};
+
+use super::CapacityExceeded;
+
+/// Bump arena that days borrow their large working buffers from, instead of
+/// each holding its own `StaticCell` for the life of the program.
+///
+/// Buffers are handed out by [`Scratch::slice`] and stay borrowed until the
+/// day's `Solution` is dropped; [`Scratch::reset`] then makes the whole arena
+/// available to the next day. Values left in the arena are never dropped.
+pub struct Scratch {
+    base: *mut u8,
+    capacity: usize,
+    used: Cell<usize>,
+}
+
+impl Scratch {
+    pub fn new<const N: usize>(buffer: &'static mut MaybeUninit<[u8; N]>) -> Self {
+        Self {
+            base: buffer.as_mut_ptr() as *mut u8,
+            capacity: N,
+            used: Cell::new(0),
+        }
+    }
+
+    /// Borrows `len` elements from the arena, building each in place with
+    /// `init`, or `None` if the arena is too small.
+    // Every call hands out a region no other borrow overlaps, and `reset`
+    // takes `&mut self` so cannot run while any of them are alive.
+    #[allow(clippy::mut_from_ref)]
+    pub fn slice<T>(&self, len: usize, mut init: impl FnMut() -> T) -> Option<&mut [T]> {
+        let base = self.base as usize;
+        let start = (base + self.used.get()).next_multiple_of(align_of::<T>()) - base;
+        let end = size_of::<T>()
+            .checked_mul(len)
+            .and_then(|size| start.checked_add(size))
+            .filter(|end| *end <= self.capacity)?;
+        let ptr = unsafe { self.base.add(start) } as *mut T;
+        for i in 0..len {
+            unsafe { ptr.add(i).write(init()) };
+        }
+        self.used.set(end);
+        Some(unsafe { core::slice::from_raw_parts_mut(ptr, len) })
+    }
+
+    /// As [`Scratch::slice`], naming the buffer in the error if the arena
+    /// is too small.
+    #[allow(clippy::mut_from_ref)]
+    pub fn slice_within<T>(
+        &self,
+        len: usize,
+        init: impl FnMut() -> T,
+        what: &'static str,
+    ) -> Result<&mut [T], CapacityExceeded> {
+        self.slice(len, init)
+            .ok_or(CapacityExceeded::new(what, self.capacity))
+    }
+
+    /// Gives the whole arena back, ready for the next day.
+    pub fn reset(&mut self) {
+        self.used.set(0);
+    }
+
+    pub fn used(&self) -> usize {
+        self.used.get()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
@@ -1,5 +1,31 @@
+use super::parse::Source;
+use super::{Answers, CapacityExceeded, DayError, DayResult, Scratch};
+
 pub trait Solution {
-    fn new() -> impl Solution;
-    fn run_sample(&mut self);
-    fn run_full(&mut self);
+    /// How long the day may run, `budget_ms` in its `capacity.toml` table
+    /// if it has one.
+    const BUDGET_MS: usize = crate::aoc::config::task::BUDGET_MS;
+
+    /// Large working buffers are borrowed from `scratch`, which is reset
+    /// once the day is done; a day whose buffers do not fit gives up on its
+    /// inputs with the error.
+    fn new(scratch: &Scratch) -> Result<impl Solution, CapacityExceeded>;
+    /// Solves the built-in sample or full input, given as `input`, recording
+    /// each part's answer in `answers` as it is logged. An input that does
+    /// not parse, has no answer or overflows a fixed-capacity container is
+    /// reported by returning a [`DayError`](super::DayError), rather than
+    /// panicking.
+    fn run_sample(&mut self, input: &[u8], answers: &mut Answers) -> DayResult;
+    fn run_full(&mut self, input: &[u8], answers: &mut Answers) -> DayResult;
+    /// Solves an input read piecewise from `source`, for inputs that arrive
+    /// over a link or are larger than free RAM. Only line-at-a-time days
+    /// support this.
+    fn run_stream(
+        &mut self,
+        _label: &'static str,
+        _source: &mut dyn Source,
+        _answers: &mut Answers,
+    ) -> DayResult {
+        Err(DayError::Unsupported("streaming"))
+    }
 }
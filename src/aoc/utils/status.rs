@@ -0,0 +1,126 @@
+//! What the run is doing right now, for status displays such as the Pico's
+//! LED. Set by [`AocTask`](crate::aoc::Task) and the days as they go, read
+//! from wherever, including other executors and the panic handler, so it is
+//! kept in atomics rather than threaded through `Solution`.
+//!
+//! The days also mark cooperative yield points with [`checkpoint`], in their
+//! longer loops, so that a watchdog can tell a day that is making progress
+//! from one that is stuck.
+
+use core::sync::atomic::{AtomicU32, AtomicU8, Ordering};
+
+use super::clock;
+use crate::aoc::results::InputKind;
+
+static DAY: AtomicU8 = AtomicU8::new(0);
+static PHASE: AtomicU8 = AtomicU8::new(Phase::Idle as u8);
+static INPUT: AtomicU8 = AtomicU8::new(InputKind::Sample as u8);
+static BUDGET_MS: AtomicU32 = AtomicU32::new(0);
+static PROGRESS: AtomicU32 = AtomicU32::new(0);
+/// [`clock::cycles`] when the day last started solving, in halves, as the
+/// M0+ has no 64-bit atomics
+static SOLVING_AT: [AtomicU32; 2] = [AtomicU32::new(0), AtomicU32::new(0)];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Phase {
+    /// Between days, or finished
+    Idle,
+    /// Reading a day's input
+    Parsing,
+    /// Working out the answers to a day's parts
+    Solving,
+    /// Something panicked; nothing more will run
+    Panicked,
+}
+
+impl Phase {
+    pub fn name(self) -> &'static str {
+        match self {
+            Phase::Idle => "idle",
+            Phase::Parsing => "parsing",
+            Phase::Solving => "solving",
+            Phase::Panicked => "panicked",
+        }
+    }
+}
+
+impl From<u8> for Phase {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => Phase::Parsing,
+            2 => Phase::Solving,
+            3 => Phase::Panicked,
+            _ => Phase::Idle,
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Phase {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "{=str}", self.name())
+    }
+}
+
+/// Records the day that is starting, 0 for none, and how long it may take.
+pub fn start_day(day: u8, budget_ms: u32) {
+    DAY.store(day, Ordering::Relaxed);
+    BUDGET_MS.store(budget_ms, Ordering::Relaxed);
+}
+
+/// The day that is running, or ran last; 0 before the first.
+pub fn day() -> u8 {
+    DAY.load(Ordering::Relaxed)
+}
+
+/// Records which of the day's inputs is starting.
+pub fn start_input(input: InputKind) {
+    INPUT.store(input as u8, Ordering::Relaxed);
+}
+
+pub fn input() -> InputKind {
+    InputKind::try_from(INPUT.load(Ordering::Relaxed)).unwrap_or(InputKind::Sample)
+}
+
+/// How long the running day may take, in milliseconds.
+pub fn budget_ms() -> u32 {
+    BUDGET_MS.load(Ordering::Relaxed)
+}
+
+/// Sets the phase, which counts as a [`checkpoint`] too.
+pub fn set(phase: Phase) {
+    if phase == Phase::Solving {
+        let now = clock::cycles();
+        SOLVING_AT[0].store(now as u32, Ordering::Relaxed);
+        SOLVING_AT[1].store((now >> 32) as u32, Ordering::Relaxed);
+    }
+    PHASE.store(phase as u8, Ordering::Relaxed);
+    checkpoint();
+}
+
+/// When the day last moved on to [`Phase::Solving`], having parsed its input,
+/// in [`clock::cycles`]. Only meaningful to the thread running the days.
+pub fn solving_at() -> u64 {
+    SOLVING_AT[0].load(Ordering::Relaxed) as u64
+        | (SOLVING_AT[1].load(Ordering::Relaxed) as u64) << 32
+}
+
+pub fn phase() -> Phase {
+    PHASE.load(Ordering::Relaxed).into()
+}
+
+/// Marks a yield point. Only the thread running the days calls this, so a
+/// plain load and store is enough, and it builds on cores without atomic
+/// read-modify-write.
+pub fn checkpoint() {
+    PROGRESS.store(
+        PROGRESS.load(Ordering::Relaxed).wrapping_add(1),
+        Ordering::Relaxed,
+    );
+}
+
+/// Counts [`checkpoint`]s; only whether it has moved on means anything.
+pub fn progress() -> u32 {
+    PROGRESS.load(Ordering::Relaxed)
+}
@@ -0,0 +1,92 @@
+//! Decoder for the inputs `build.rs` packs with the `compress-inputs`
+//! feature.
+//!
+//! A packed input is the unpacked length as 4 little-endian bytes, then a
+//! run of tokens, each starting with a control byte `c`:
+//! * `c < 0x80`: the next `c + 1` bytes are copied as they are
+//! * otherwise: `(c & 0x7F) + MIN_MATCH` bytes are copied from earlier in
+//!   the output, at the distance given by the next 2 little-endian bytes
+
+/// Shortest repeat worth a back-reference; anything shorter costs as much as
+/// the literal bytes.
+pub const MIN_MATCH: usize = 3;
+
+/// Length of the input once unpacked, read from the header.
+pub fn unpacked_len(packed: &[u8]) -> Option<usize> {
+    let header = packed.get(..4)?;
+    Some(u32::from_le_bytes(header.try_into().ok()?) as usize)
+}
+
+/// Streams `packed` into `output`, which must be exactly `unpacked_len`
+/// bytes long. Returns `None` if the data is malformed.
+pub fn unpack(packed: &[u8], output: &mut [u8]) -> Option<()> {
+    let mut input = packed.get(4..)?.iter().copied();
+    let mut written = 0;
+    while let Some(control) = input.next() {
+        if control < 0x80 {
+            let end = written + control as usize + 1;
+            for byte in output.get_mut(written..end)? {
+                *byte = input.next()?;
+            }
+            written = end;
+        } else {
+            let len = (control & 0x7F) as usize + MIN_MATCH;
+            let distance = u16::from_le_bytes([input.next()?, input.next()?]) as usize;
+            let start = written.checked_sub(distance).filter(|_| distance != 0)?;
+            if written + len > output.len() {
+                return None;
+            }
+            // Byte by byte, as a match may overlap the bytes it produces
+            for i in 0..len {
+                output[written + i] = output[start + i];
+            }
+            written += len;
+        }
+    }
+    (written == output.len()).then_some(())
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::vec;
+    use std::vec::Vec;
+
+    use super::*;
+
+    include!("../../../build/pack.rs");
+
+    fn round_trip(data: &[u8]) {
+        let packed = pack(data);
+        assert_eq!(unpacked_len(&packed), Some(data.len()));
+        let mut output = vec![0; data.len()];
+        assert_eq!(unpack(&packed, &mut output), Some(()));
+        assert_eq!(output, data);
+    }
+
+    #[test]
+    fn round_trips() {
+        round_trip(b"");
+        round_trip(b"ab");
+        round_trip(b"3   4\n4   3\n2   5\n1   3\n3   9\n3   3\n");
+        // Overlapping matches, and runs longer than a single token holds
+        round_trip(&[b'.'; 1000]);
+        // Literal runs longer than a single token holds
+        let noise: Vec<u8> = (0..1000_u32).map(|i| (i * 7919 % 251) as u8).collect();
+        round_trip(&noise);
+        // Repeats further back than the window reaches
+        let far: Vec<u8> = (0..70_000_u32).map(|i| (i % 65_537 % 253) as u8).collect();
+        round_trip(&far);
+    }
+
+    #[test]
+    fn rejects_truncated() {
+        let data = b"0123456789 0123456789 0123456789\n";
+        let packed = pack(data);
+        let mut output = [0; 33];
+        assert_eq!(unpack(&packed[..packed.len() - 1], &mut output), None);
+        assert_eq!(unpack(&packed[..2], &mut output), None);
+        let mut short = [0; 32];
+        assert_eq!(unpack(&packed, &mut short), None);
+    }
+}
@@ -4,11 +4,19 @@
 
 #![no_std] // don't link the Rust standard library
 #![no_main] // disable all Rust-level entry points
+#![feature(lang_items)]
+#![allow(internal_features)] // for `eh_personality`, below
 #![cfg(target_os = "linux")]
 
 use core::arch::asm;
 use core::panic::PanicInfo;
 
+use embassy_runner::aoc;
+use embassy_runner::crash;
+// Linking the library brings in its mmap-backed global allocator
+#[cfg(feature = "alloc")]
+use embassy_runner::heap;
+
 // https://man7.org/linux/man-pages/man2/write.2.html
 // ```c
 // ssize_t write(int fd, const void buf[.count], size_t count);
@@ -47,22 +55,62 @@ fn sys_exit(status: i32) -> ! {
     }
 }
 
-#[no_mangle] // don't mangle the name of this function
-pub extern "C" fn _start() -> ! {
-    // this function is the entry point, since the linker looks for a function
-    // named `_start` by default
+struct StdoutLogger;
+
+impl log::Log for StdoutLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::Level::Info
+    }
 
-    let msg = b"Hello, world!\n";
-    let ret = sys_write(1, msg.as_ptr(), msg.len());
-    if ret != msg.len() {
-        sys_exit(1);
+    fn log(&self, record: &log::Record) {
+        use core::fmt::Write;
+        if self.enabled(record.metadata()) {
+            let mut buf = arrayvec::ArrayString::<1024>::new();
+            let _ = writeln!(buf, "{} - {}", record.level(), record.args());
+            sys_write(1, buf.as_ptr(), buf.len());
+        }
     }
 
+    fn flush(&self) {}
+}
+static LOGGER: StdoutLogger = StdoutLogger;
+
+// The linker looks for `_start` as the entry point. The kernel enters it
+// with the stack 16-byte aligned, but a function expects it 8 bytes short of
+// that, as a call leaves it, so it is only a trampoline into `start`.
+core::arch::global_asm!(
+    ".globl _start",
+    "_start:",
+    "xor ebp, ebp",
+    "and rsp, -16",
+    "call {start}",
+    start = sym start,
+);
+
+extern "C" fn start() -> ! {
+    #[cfg(feature = "alloc")]
+    heap::init();
+    let _ = log::set_logger(&LOGGER).map(|()| log::set_max_level(log::LevelFilter::Info));
+
+    let mut aoc = aoc::Task::new();
+    aoc.run();
+
     sys_exit(0);
 }
 
-/// This function is called on panic.
+/// Panics abort, but the prebuilt `core` still refers to the unwinder's
+/// personality routine, which `std` would otherwise provide.
+#[lang = "eh_personality"]
+fn eh_personality() {}
+
+/// This function is called on panic. Reports the crash on stderr and exits
+/// with a failure status, rather than hanging.
 #[panic_handler]
-fn panic(_info: &PanicInfo) -> ! {
-    loop {}
+fn panic(info: &PanicInfo) -> ! {
+    use core::fmt::Write;
+
+    let mut msg = arrayvec::ArrayString::<512>::new();
+    let _ = writeln!(msg, "{}", crash::Crash::capture(info));
+    sys_write(2, msg.as_ptr(), msg.len());
+    sys_exit(1);
 }
@@ -9,6 +9,7 @@ use core::panic::PanicInfo;
 
 use windows_sys::Win32::System::Console::GetStdHandle;
 use windows_sys::Win32::System::Console::WriteConsoleA;
+use windows_sys::Win32::System::Console::STD_ERROR_HANDLE;
 use windows_sys::Win32::System::Console::STD_OUTPUT_HANDLE;
 use windows_sys::Win32::System::Threading::ExitProcess;
 
@@ -17,10 +18,23 @@ use windows_sys::Win32::System::Threading::ExitProcess;
 //use windows_sys::Win32::System::Console::ATTACH_PARENT_PROCESS;
 
 use embassy_runner::aoc;
+use embassy_runner::crash;
 
+/// Reports the crash on the console's error output before exiting.
 #[panic_handler]
-fn panic(_: &PanicInfo<'_>) -> ! {
+fn panic(info: &PanicInfo<'_>) -> ! {
+    use core::fmt::Write;
+
+    let mut buf: arrayvec::ArrayString<512> = arrayvec::ArrayString::new();
+    let _ = writeln!(buf, "{}", crash::Crash::capture(info));
     unsafe {
+        WriteConsoleA(
+            GetStdHandle(STD_ERROR_HANDLE),
+            buf.as_ptr().cast::<c_void>(),
+            buf.len() as u32,
+            core::ptr::null_mut(),
+            core::ptr::null(),
+        );
         ExitProcess(1);
     }
 }
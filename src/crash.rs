@@ -0,0 +1,153 @@
+//! Crash records: what panicked, where, and what the run was doing at the
+//! time, captured by the panic handler.
+//!
+//! On the Pico the record is kept in a slot of RAM that the runtime leaves
+//! uninitialised, which survives a reset, so it can be reported on the next
+//! boot through whichever logging channel that build has, probe or not. On
+//! Linux and Windows there is no next boot; the panic handlers print the
+//! record straight away and exit.
+
+use core::fmt::{self, Write};
+use core::panic::PanicInfo;
+
+use arrayvec::ArrayString;
+
+use crate::aoc::status::{self, Phase};
+
+const MAX_FILE: usize = 64;
+const MAX_MESSAGE: usize = 128;
+
+pub struct Crash {
+    /// The day running, 0 if none had started
+    pub day: u8,
+    pub phase: Phase,
+    pub file: ArrayString<MAX_FILE>,
+    pub line: u32,
+    pub message: ArrayString<MAX_MESSAGE>,
+}
+
+impl Crash {
+    pub fn capture(info: &PanicInfo) -> Self {
+        let mut crash = Crash {
+            day: status::day(),
+            phase: status::phase(),
+            file: ArrayString::new(),
+            line: 0,
+            message: ArrayString::new(),
+        };
+        if let Some(location) = info.location() {
+            let _ = Truncate(&mut crash.file).write_str(location.file());
+            crash.line = location.line();
+        }
+        let _ = write!(Truncate(&mut crash.message), "{}", info.message());
+        crash
+    }
+}
+
+impl fmt::Display for Crash {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "day{} {} panicked at {}:{}: {}",
+            self.day,
+            self.phase.name(),
+            self.file,
+            self.line,
+            self.message
+        )
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Crash {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "day{} {} panicked at {=str}:{}: {=str}",
+            self.day,
+            self.phase,
+            self.file.as_str(),
+            self.line,
+            self.message.as_str()
+        )
+    }
+}
+
+/// Keeps as much of what is written as fits, rather than failing.
+struct Truncate<'a, const N: usize>(&'a mut ArrayString<N>);
+
+impl<const N: usize> Write for Truncate<'_, N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            if self.0.try_push(c).is_err() {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(target_arch = "arm", target_os = "none"))]
+mod imp {
+    use core::mem::MaybeUninit;
+    use core::ptr::{addr_of, addr_of_mut};
+
+    use super::{Crash, MAX_FILE, MAX_MESSAGE};
+
+    /// Marks a slot holding a record, rather than whatever RAM powered up as.
+    const MAGIC: u32 = 0xDEAD_DA75;
+    /// Magic, day, phase, file and message lengths, line, file, message.
+    const SIZE: usize = 12 + MAX_FILE + MAX_MESSAGE;
+
+    #[link_section = ".uninit.CRASH"]
+    static mut SLOT: MaybeUninit<[u8; SIZE]> = MaybeUninit::uninit();
+
+    pub fn save(crash: &Crash) {
+        let mut bytes = [0; SIZE];
+        bytes[..4].copy_from_slice(&MAGIC.to_le_bytes());
+        bytes[4] = crash.day;
+        bytes[5] = crash.phase as u8;
+        bytes[6] = crash.file.len() as u8;
+        bytes[7] = crash.message.len() as u8;
+        bytes[8..12].copy_from_slice(&crash.line.to_le_bytes());
+        bytes[12..12 + crash.file.len()].copy_from_slice(crash.file.as_bytes());
+        let message = 12 + MAX_FILE;
+        bytes[message..message + crash.message.len()].copy_from_slice(crash.message.as_bytes());
+        unsafe {
+            addr_of_mut!(SLOT)
+                .cast::<[u8; SIZE]>()
+                .write_volatile(bytes)
+        }
+    }
+
+    /// The record left by the last run, if it panicked. Each record is only
+    /// reported once.
+    pub fn take() -> Option<Crash> {
+        let bytes = unsafe { addr_of!(SLOT).cast::<[u8; SIZE]>().read_volatile() };
+        unsafe { addr_of_mut!(SLOT).cast::<u32>().write_volatile(0) }
+        if bytes[..4] != MAGIC.to_le_bytes() {
+            return None;
+        }
+        let (file_len, message_len) = (bytes[6] as usize, bytes[7] as usize);
+        if file_len > MAX_FILE || message_len > MAX_MESSAGE {
+            return None;
+        }
+        let message = 12 + MAX_FILE;
+        Some(Crash {
+            day: bytes[4],
+            phase: bytes[5].into(),
+            file: core::str::from_utf8(&bytes[12..12 + file_len])
+                .ok()?
+                .try_into()
+                .ok()?,
+            line: u32::from_le_bytes(bytes[8..12].try_into().unwrap()),
+            message: core::str::from_utf8(&bytes[message..message + message_len])
+                .ok()?
+                .try_into()
+                .ok()?,
+        })
+    }
+}
+
+#[cfg(all(target_arch = "arm", target_os = "none"))]
+pub use imp::{save, take};
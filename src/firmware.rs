@@ -0,0 +1,75 @@
+//! cyw43 firmware read from its own flash partitions, enabled with the
+//! `firmware-partition` feature. The partitions are reserved by `build.rs`
+//! and written once with `cargo xtask flash-firmware`, so flashing the
+//! application no longer rewrites ~230K of firmware every time.
+//!
+//! Each partition holds a blob as written by the xtask: [`MAGIC`], the blob's
+//! length and its FNV-1a checksum, both 32-bit little-endian, then the blob.
+
+/// Marks a partition the xtask has written.
+const MAGIC: [u8; 4] = *b"CYW4";
+const HEADER_SIZE: usize = 12;
+
+extern "C" {
+    static __cyw43_fw_start: u8;
+    static __cyw43_fw_end: u8;
+    static __cyw43_clm_start: u8;
+    static __cyw43_clm_end: u8;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, defmt::Format)]
+pub enum Error {
+    /// No blob has been flashed, or the partition was erased
+    Missing,
+    /// The header claims more bytes than the partition holds
+    TooLong(usize),
+    /// The blob is not the one the header describes, e.g. half written
+    Checksum,
+}
+
+/// The firmware blob, `43439A0.bin`.
+pub fn firmware() -> Result<&'static [u8], Error> {
+    unsafe {
+        blob(
+            core::ptr::addr_of!(__cyw43_fw_start),
+            core::ptr::addr_of!(__cyw43_fw_end),
+        )
+    }
+}
+
+/// The country locale matrix blob, `43439A0_clm.bin`.
+pub fn clm() -> Result<&'static [u8], Error> {
+    unsafe {
+        blob(
+            core::ptr::addr_of!(__cyw43_clm_start),
+            core::ptr::addr_of!(__cyw43_clm_end),
+        )
+    }
+}
+
+/// Checks the header of the partition from `start` to `end`, returning the
+/// blob that follows it.
+///
+/// # Safety
+/// `start..end` must be readable for the life of the program, as
+/// memory-mapped flash is.
+unsafe fn blob(start: *const u8, end: *const u8) -> Result<&'static [u8], Error> {
+    let partition = core::slice::from_raw_parts(start, end as usize - start as usize);
+    let (header, contents) = partition.split_at(HEADER_SIZE);
+    if header[..4] != MAGIC {
+        return Err(Error::Missing);
+    }
+    let word = |at: usize| u32::from_le_bytes(header[at..at + 4].try_into().unwrap());
+    let len = word(4) as usize;
+    let blob = contents.get(..len).ok_or(Error::TooLong(len))?;
+    if fnv1a(blob) != word(8) {
+        return Err(Error::Checksum);
+    }
+    Ok(blob)
+}
+
+fn fnv1a(data: &[u8]) -> u32 {
+    data.iter().fold(0x811c_9dc5, |hash, byte| {
+        (hash ^ *byte as u32).wrapping_mul(0x0100_0193)
+    })
+}
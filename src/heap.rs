@@ -0,0 +1,234 @@
+//! Global allocator, enabled with the `alloc` feature so that days may use
+//! `alloc::vec::Vec`, `alloc::collections::BTreeMap` and friends instead of
+//! fixed capacity containers.
+//!
+//! On the Pico the heap is an `embedded_alloc` heap over the `.heap` region
+//! that `build.rs` reserves in RAM (sized by the `HEAP_SIZE` environment
+//! variable at build time). On Linux it is a bump allocator over an
+//! anonymous `mmap` arena.
+//!
+//! Both note the most they have had in use at once, so that a day's peak
+//! usage can be reported after it has dropped its collections.
+
+#[cfg(all(target_arch = "arm", target_os = "none"))]
+mod imp {
+    use core::alloc::{GlobalAlloc, Layout};
+
+    use embedded_alloc::LlffHeap as Heap;
+    use portable_atomic::{AtomicUsize, Ordering};
+
+    #[global_allocator]
+    static HEAP: Tracked = Tracked {
+        heap: Heap::empty(),
+        peak: AtomicUsize::new(0),
+    };
+
+    /// The heap, and the most of it in use at once since the last
+    /// [`reset_peak`].
+    struct Tracked {
+        heap: Heap,
+        peak: AtomicUsize,
+    }
+
+    unsafe impl GlobalAlloc for Tracked {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let ptr = self.heap.alloc(layout);
+            self.peak.fetch_max(self.heap.used(), Ordering::Relaxed);
+            ptr
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            self.heap.dealloc(ptr, layout)
+        }
+    }
+
+    extern "C" {
+        static mut _heap_start: u8;
+        static mut _heap_end: u8;
+    }
+
+    /// Hands the linker-reserved `.heap` region to the allocator.
+    /// Must be called exactly once, before anything allocates.
+    pub fn init() {
+        let start = core::ptr::addr_of_mut!(_heap_start) as usize;
+        let end = core::ptr::addr_of_mut!(_heap_end) as usize;
+        unsafe { HEAP.heap.init(start, end - start) }
+    }
+
+    pub fn used() -> usize {
+        HEAP.heap.used()
+    }
+
+    pub fn free() -> usize {
+        HEAP.heap.free()
+    }
+
+    pub fn peak() -> usize {
+        HEAP.peak.load(Ordering::Relaxed)
+    }
+
+    /// Starts the high-water mark again from what is in use now.
+    pub fn reset_peak() {
+        HEAP.peak.store(used(), Ordering::Relaxed);
+    }
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+mod imp {
+    use core::alloc::{GlobalAlloc, Layout};
+    use core::arch::asm;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Address space reserved on first use; pages are only backed by memory
+    /// once touched, so this can be generous.
+    const ARENA_SIZE: usize = 1 << 30;
+
+    const PROT_READ: usize = 0x1;
+    const PROT_WRITE: usize = 0x2;
+    const MAP_PRIVATE: usize = 0x02;
+    const MAP_ANONYMOUS: usize = 0x20;
+    const MAP_NORESERVE: usize = 0x4000;
+
+    #[global_allocator]
+    static HEAP: Bump = Bump {
+        base: AtomicUsize::new(0),
+        next: AtomicUsize::new(0),
+        live: AtomicUsize::new(0),
+        peak: AtomicUsize::new(0),
+    };
+
+    /// Hands out memory by bumping an offset. Only the most recent allocation
+    /// is ever given back, which suits the allocate-solve-drop shape of a day.
+    struct Bump {
+        base: AtomicUsize,
+        next: AtomicUsize,
+        /// Bytes allocated and not yet given back, which `next` overstates
+        /// once anything but the latest allocation is freed
+        live: AtomicUsize,
+        /// Most `live` has been since the last [`reset_peak`]
+        peak: AtomicUsize,
+    }
+
+    // https://man7.org/linux/man-pages/man2/mmap.2.html
+    // ```c
+    // void *mmap(void addr[.length], size_t length, int prot, int flags, int fd, off_t offset);
+    // ```
+    fn sys_mmap(length: usize, prot: usize, flags: usize) -> usize {
+        unsafe {
+            let ret: usize;
+
+            asm!(
+                "syscall",
+                in("rax") 9,
+                in("rdi") 0,
+                in("rsi") length,
+                in("rdx") prot,
+                in("r10") flags,
+                in("r8") usize::MAX,
+                in("r9") 0,
+                lateout("rax") ret,
+                out("rcx") _,
+                out("r11") _,
+            );
+
+            ret
+        }
+    }
+
+    impl Bump {
+        fn base(&self) -> Option<usize> {
+            match self.base.load(Ordering::Acquire) {
+                0 => {}
+                base => return Some(base),
+            }
+            let base = sys_mmap(
+                ARENA_SIZE,
+                PROT_READ | PROT_WRITE,
+                MAP_PRIVATE | MAP_ANONYMOUS | MAP_NORESERVE,
+            );
+            // Errors come back as -errno
+            if base > usize::MAX - 4096 {
+                return None;
+            }
+            Some(
+                match self
+                    .base
+                    .compare_exchange(0, base, Ordering::AcqRel, Ordering::Acquire)
+                {
+                    Ok(_) => base,
+                    Err(existing) => existing,
+                },
+            )
+        }
+    }
+
+    unsafe impl GlobalAlloc for Bump {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let Some(base) = self.base() else {
+                return core::ptr::null_mut();
+            };
+            let mut next = self.next.load(Ordering::Relaxed);
+            loop {
+                let start = (base + next).next_multiple_of(layout.align()) - base;
+                let end = start + layout.size();
+                if end > ARENA_SIZE {
+                    return core::ptr::null_mut();
+                }
+                match self.next.compare_exchange_weak(
+                    next,
+                    end,
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        let live = self.live.fetch_add(layout.size(), Ordering::Relaxed);
+                        self.peak.fetch_max(live + layout.size(), Ordering::Relaxed);
+                        return (base + start) as *mut u8;
+                    }
+                    Err(current) => next = current,
+                }
+            }
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            self.live.fetch_sub(layout.size(), Ordering::Relaxed);
+            let start = ptr as usize - self.base.load(Ordering::Relaxed);
+            // Only succeeds if this was the most recent allocation
+            let _ = self.next.compare_exchange(
+                start + layout.size(),
+                start,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            );
+        }
+    }
+
+    /// The arena is mapped lazily on first allocation.
+    pub fn init() {}
+
+    pub fn used() -> usize {
+        HEAP.live.load(Ordering::Relaxed)
+    }
+
+    /// What is left of the arena, freed space short of its end not counting.
+    pub fn free() -> usize {
+        ARENA_SIZE - HEAP.next.load(Ordering::Relaxed)
+    }
+
+    pub fn peak() -> usize {
+        HEAP.peak.load(Ordering::Relaxed)
+    }
+
+    /// Starts the high-water mark again from what is in use now.
+    pub fn reset_peak() {
+        HEAP.peak.store(used(), Ordering::Relaxed);
+    }
+}
+
+#[cfg(not(any(
+    all(target_arch = "arm", target_os = "none"),
+    all(target_os = "linux", target_arch = "x86_64")
+)))]
+compile_error!("The `alloc` feature has no allocator for this target.");
+
+pub use imp::{free, init, peak, reset_peak, used};
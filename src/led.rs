@@ -0,0 +1,92 @@
+//! The board LED. On a Pico W it hangs off the cyw43 chip's GPIO0, so it is
+//! only usable once the chip is up; on a plain Pico it is GPIO25, which the
+//! Pico W uses as the cyw43 SPI chip select instead.
+//!
+//! [`show_status`] turns it into the only status display a board without a
+//! probe has:
+//! * as each day starts, its number as that many short blinks
+//! * a slow blink while the day parses its input, a fast one while it solves
+//! * off between days and once everything has run
+//! * SOS, repeated, after a panic
+
+#[cfg(not(feature = "wifi"))]
+use embassy_rp::{gpio::Output, peripherals::PIN_25};
+use embassy_time::{Duration, Timer};
+
+use crate::aoc::status::{self, Phase};
+
+/// Length of a morse dot, and of the blinks counting out a day's number.
+const DOT: Duration = Duration::from_millis(150);
+const SLOW: Duration = Duration::from_millis(500);
+const FAST: Duration = Duration::from_millis(100);
+/// How often an idle LED looks for something to show.
+const POLL: Duration = Duration::from_millis(50);
+
+pub struct Led {
+    #[cfg(feature = "wifi")]
+    control: cyw43::Control<'static>,
+    #[cfg(not(feature = "wifi"))]
+    pin: Output<'static, PIN_25>,
+}
+
+impl Led {
+    #[cfg(feature = "wifi")]
+    pub fn new(control: cyw43::Control<'static>) -> Self {
+        Self { control }
+    }
+
+    #[cfg(not(feature = "wifi"))]
+    pub fn new(pin: Output<'static, PIN_25>) -> Self {
+        Self { pin }
+    }
+
+    pub async fn set(&mut self, on: bool) {
+        #[cfg(feature = "wifi")]
+        self.control.gpio_set(0, on).await;
+        #[cfg(not(feature = "wifi"))]
+        self.pin.set_level(on.into());
+    }
+
+    /// `count` blinks, each on for `on` then off for `off`.
+    async fn blink(&mut self, count: u8, on: Duration, off: Duration) {
+        for _ in 0..count {
+            self.set(true).await;
+            Timer::after(on).await;
+            self.set(false).await;
+            Timer::after(off).await;
+        }
+    }
+
+    /// ··· ——— ···, then a word gap.
+    async fn sos(&mut self) {
+        self.blink(3, DOT, DOT).await;
+        Timer::after(DOT * 2).await;
+        self.blink(3, DOT * 3, DOT).await;
+        Timer::after(DOT * 2).await;
+        self.blink(3, DOT, DOT).await;
+        Timer::after(DOT * 6).await;
+    }
+}
+
+/// Shows the run's [`status`] on `led`, forever. Run it on an executor of its
+/// own, as the days do not yield to the one running them.
+pub async fn show_status(mut led: Led) -> ! {
+    let mut shown_day = 0;
+    loop {
+        let day = status::day();
+        match status::phase() {
+            Phase::Panicked => led.sos().await,
+            _ if day != shown_day => {
+                shown_day = day;
+                led.blink(day, DOT, DOT).await;
+                Timer::after(SLOW * 2).await;
+            }
+            Phase::Idle => {
+                led.set(false).await;
+                Timer::after(POLL).await;
+            }
+            Phase::Parsing => led.blink(1, SLOW, SLOW).await,
+            Phase::Solving => led.blink(1, FAST, FAST).await,
+        }
+    }
+}
@@ -1,5 +1,8 @@
 #![no_std]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
 #[cfg(all(target_arch = "arm", target_os = "none"))]
 use embassy_rp::{
     bind_interrupts,
@@ -13,6 +16,9 @@ bind_interrupts!(pub struct Irqs {
 });
 
 pub mod aoc;
+pub mod crash;
+#[cfg(feature = "alloc")]
+pub mod heap;
 
 #[macro_use]
 pub mod fmt;
@@ -4,26 +4,58 @@
 #![cfg(all(target_arch = "arm", target_os = "none"))]
 
 extern crate core;
+#[cfg(feature = "alloc")]
+extern crate alloc;
 
 use defmt::unwrap;
 use defmt_rtt as _;
-use embassy_executor::Spawner;
-use panic_probe as _;
+use embassy_executor::{InterruptExecutor, Spawner};
 
 mod aoc;
+mod crash;
+#[cfg(feature = "firmware-partition")]
+mod firmware;
 mod fmt;
+#[cfg(feature = "alloc")]
+mod heap;
+mod led;
+#[cfg(feature = "profile")]
+mod profile;
+mod results_log;
+mod watchdog;
 
+#[cfg(feature = "wifi")]
 use cyw43_pio::PioSpi;
+#[cfg(feature = "wifi")]
 use embassy_rp::bind_interrupts;
 use embassy_rp::gpio::{Level, Output};
-use embassy_rp::peripherals::{DMA_CH0, PIN_23, PIN_25, PIO0};
+use embassy_rp::interrupt;
+use embassy_rp::interrupt::{InterruptExt, Priority};
+#[cfg(feature = "wifi")]
+use embassy_rp::peripherals::{DMA_CH0, PIN_23, PIN_24, PIN_29, PIO0};
+use embassy_rp::peripherals::{PIN_25, USB};
+#[cfg(feature = "wifi")]
 use embassy_rp::pio::{InterruptHandler, Pio};
+use embassy_rp::watchdog::Watchdog;
 use static_cell::StaticCell;
 
+use crate::aoc::results::{self, Outcome, Record, ResultsLog};
+
+#[cfg(feature = "wifi")]
 bind_interrupts!(struct Irqs {
     PIO0_IRQ_0 => InterruptHandler<PIO0>;
 });
 
+/// Runs the LED, and the cyw43 chip behind it, above thread mode, where the
+/// days run without ever yielding.
+static EXECUTOR_LED: InterruptExecutor = InterruptExecutor::new();
+
+#[interrupt]
+unsafe fn SWI_IRQ_1() {
+    EXECUTOR_LED.on_interrupt()
+}
+
+#[cfg(feature = "wifi")]
 #[embassy_executor::task]
 async fn wifi_task(
     runner: cyw43::Runner<
@@ -35,35 +67,49 @@ async fn wifi_task(
     runner.run().await
 }
 
-#[embassy_executor::main]
-async fn main(#[allow(unused_variables)] spawner: Spawner) {
-    let p = embassy_rp::init(Default::default());
-    let fw = include_bytes!("../cyw43-firmware/43439A0.bin");
-    let clm = include_bytes!("../cyw43-firmware/43439A0_clm.bin");
-
-    // To make flashing faster for development, you may want to flash the firmwares independently
-    // at hardcoded addresses, instead of baking them into the program with `include_bytes!`:
-    //     probe-rs download 43439A0.bin --format bin --chip RP2040 --base-address 0x10100000
-    //     probe-rs download 43439A0_clm.bin --format bin --chip RP2040 --base-address 0x10140000
-    //let fw = unsafe { core::slice::from_raw_parts(0x10100000 as *const u8, 230321) };
-    //let clm = unsafe { core::slice::from_raw_parts(0x10140000 as *const u8, 4752) };
-
-    let pwr = Output::new(p.PIN_23, Level::Low);
-    let cs = Output::new(p.PIN_25, Level::High);
-    let mut pio = Pio::new(p.PIO0, Irqs);
+/// The pins and peripherals the cyw43 chip hangs off on a Pico W.
+#[cfg(feature = "wifi")]
+struct Wifi {
+    pwr: PIN_23,
+    cs: PIN_25,
+    dio: PIN_24,
+    clk: PIN_29,
+    pio: PIO0,
+    dma: DMA_CH0,
+}
+
+#[cfg(feature = "wifi")]
+#[embassy_executor::task]
+async fn led_task(wifi: Wifi) -> ! {
+    #[cfg(not(feature = "firmware-partition"))]
+    let (fw, clm) = (
+        include_bytes!("../cyw43-firmware/43439A0.bin"),
+        include_bytes!("../cyw43-firmware/43439A0_clm.bin"),
+    );
+
+    // To make flashing faster for development, build with the
+    // `firmware-partition` feature and flash the firmwares independently,
+    // once, with `cargo xtask flash-firmware`
+    #[cfg(feature = "firmware-partition")]
+    let (fw, clm) = (unwrap!(firmware::firmware()), unwrap!(firmware::clm()));
+
+    let pwr = Output::new(wifi.pwr, Level::Low);
+    let cs = Output::new(wifi.cs, Level::High);
+    let mut pio = Pio::new(wifi.pio, Irqs);
     let spi = PioSpi::new(
         &mut pio.common,
         pio.sm0,
         pio.irq0,
         cs,
-        p.PIN_24,
-        p.PIN_29,
-        p.DMA_CH0,
+        wifi.dio,
+        wifi.clk,
+        wifi.dma,
     );
 
     static STATE: StaticCell<cyw43::State> = StaticCell::new();
     let state = STATE.init(cyw43::State::new());
     let (_net_device, mut control, runner) = cyw43::new(state, pwr, spi, fw).await;
+    let spawner = Spawner::for_current_executor().await;
     unwrap!(spawner.spawn(wifi_task(runner)));
 
     control.init(clm).await;
@@ -71,13 +117,93 @@ async fn main(#[allow(unused_variables)] spawner: Spawner) {
         .set_power_management(cyw43::PowerManagementMode::PowerSave)
         .await;
 
-    info!("led on!");
-    control.gpio_set(0, true).await;
+    led::show_status(led::Led::new(control)).await
+}
+
+#[cfg(not(feature = "wifi"))]
+#[embassy_executor::task]
+async fn led_task(pin: PIN_25) -> ! {
+    led::show_status(led::Led::new(Output::new(pin, Level::Low))).await
+}
+
+#[embassy_executor::task]
+async fn watchdog_task(watchdog: Watchdog) -> ! {
+    watchdog::guard(watchdog).await
+}
+
+#[embassy_executor::task]
+async fn results_task(usb: USB, log: &'static mut results_log::FlashLog) -> ! {
+    results_log::serve(usb, log).await
+}
+
+#[embassy_executor::main]
+async fn main(spawner: Spawner) {
+    #[cfg(feature = "alloc")]
+    heap::init();
+    let p = embassy_rp::init(Default::default());
+    #[cfg(feature = "profile")]
+    profile::start(unwrap!(cortex_m::Peripherals::take()).SYST);
+    if let Some(crash) = crash::take() {
+        error!("previous run: {}", crash);
+    }
+    let mut wd = Watchdog::new(p.WATCHDOG);
+    let timed_out = watchdog::take_timed_out(&mut wd);
+    if let Some(timed_out) = &timed_out {
+        error!("previous run: day{} timed out", timed_out.day);
+    }
+
+    interrupt::SWI_IRQ_1.set_priority(Priority::P3);
+    let led_spawner = EXECUTOR_LED.start(interrupt::SWI_IRQ_1);
+    #[cfg(feature = "wifi")]
+    unwrap!(led_spawner.spawn(led_task(Wifi {
+        pwr: p.PIN_23,
+        cs: p.PIN_25,
+        dio: p.PIN_24,
+        clk: p.PIN_29,
+        pio: p.PIO0,
+        dma: p.DMA_CH0,
+    })));
+    #[cfg(not(feature = "wifi"))]
+    unwrap!(led_spawner.spawn(led_task(p.PIN_25)));
+    unwrap!(led_spawner.spawn(watchdog_task(wd)));
+
+    static LOG: StaticCell<results_log::FlashLog> = StaticCell::new();
+    let log = LOG.init(results_log::FlashLog::new(p.FLASH));
 
     let mut aoc = aoc::Task::new();
+    if let Some(timed_out) = timed_out {
+        log.append(&Record {
+            day: timed_out.day,
+            input: timed_out.input,
+            outcome: Outcome::TimedOut,
+            micros: timed_out.budget_ms as u64 * 1000,
+            build: results::build_id(),
+            answers: Default::default(),
+        });
+        // It would only time out again
+        aoc.skip(timed_out.day);
+    }
+    aoc.log_to(&mut *log);
 
     aoc.run();
+    info!("all days run");
+    #[cfg(feature = "profile")]
+    profile::dump();
+
+    // The days have had the log; now let it be read
+    unwrap!(spawner.spawn(results_task(p.USB, log)));
+}
 
-    info!("led off!");
-    control.gpio_set(0, false).await;
+/// Logs the panic, keeps a record of it to report on the next boot and
+/// leaves the LED signalling SOS. A panic in a day only stops thread mode, so
+/// the LED executor carries on to show it.
+#[panic_handler]
+fn panic(info: &core::panic::PanicInfo) -> ! {
+    let crash = crash::Crash::capture(info);
+    error!("{}", crash);
+    crash::save(&crash);
+    aoc::status::set(aoc::status::Phase::Panicked);
+    loop {
+        cortex_m::asm::wfi();
+    }
 }
@@ -0,0 +1,89 @@
+//! A sampling profiler. SysTick interrupts the core [`SAMPLE_HZ`] times a
+//! second and, while a day is parsing or solving, the address it interrupted
+//! goes into a ring of the last [`SAMPLES`] samples. SysTick runs at the top
+//! priority, so code in the other interrupts is sampled too.
+//!
+//! Once the days have run, [`dump`] logs the samples, for
+//! `cargo xtask profile` to turn into a flat per-function profile with
+//! `linker.map`.
+
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use cortex_m::peripheral::syst::SystClkSource;
+use cortex_m::peripheral::SYST;
+
+use crate::aoc::status::{self, Phase};
+use crate::info;
+
+/// Samples kept, the most recent, in 4 bytes of RAM each.
+const SAMPLES: usize = 2048;
+/// Prime, so sampling does not keep step with work done every millisecond.
+const SAMPLE_HZ: u32 = 997;
+/// Samples per log line.
+const CHUNK: usize = 16;
+
+static RING: [AtomicU32; SAMPLES] = [const { AtomicU32::new(0) }; SAMPLES];
+/// Samples taken in all, which may be more than the ring holds.
+static TAKEN: AtomicU32 = AtomicU32::new(0);
+
+// SysTick is entered with the interrupted code's registers stacked on
+// whichever stack it was using; hand that frame on to `sample`. Only the
+// handler's own stack pointer is MSP, so bit 2 of EXC_RETURN picks the one.
+core::arch::global_asm!(
+    ".section .text.SysTick, \"ax\"",
+    ".global SysTick",
+    ".type SysTick, %function",
+    ".thumb_func",
+    "SysTick:",
+    "    mov r0, lr",
+    "    movs r1, #4",
+    "    tst r0, r1",
+    "    mrs r0, msp",
+    "    beq 1f",
+    "    mrs r0, psp",
+    "1:",
+    "    ldr r1, ={sample}",
+    "    bx r1",
+    ".ltorg",
+    sample = sym sample,
+);
+
+/// Records the PC in the exception `frame`: r0-r3, r12, lr, pc, xpsr.
+unsafe extern "C" fn sample(frame: *const u32) {
+    if !matches!(status::phase(), Phase::Parsing | Phase::Solving) {
+        return;
+    }
+    let pc = unsafe { frame.add(6).read_volatile() };
+    // Only this handler writes, so a plain load and store is enough
+    let taken = TAKEN.load(Ordering::Relaxed);
+    RING[taken as usize % SAMPLES].store(pc, Ordering::Relaxed);
+    TAKEN.store(taken.wrapping_add(1), Ordering::Relaxed);
+}
+
+/// Starts sampling, off the core clock.
+pub fn start(mut syst: SYST) {
+    syst.set_clock_source(SystClkSource::Core);
+    syst.set_reload(embassy_rp::clocks::clk_sys_freq() / SAMPLE_HZ - 1);
+    syst.clear_current();
+    syst.enable_interrupt();
+    syst.enable_counter();
+}
+
+/// Logs the samples kept, as `profile samples [...]` lines.
+pub fn dump() {
+    let taken = TAKEN.load(Ordering::Relaxed) as usize;
+    let kept = taken.min(SAMPLES);
+    info!(
+        "profile: {} samples at {}Hz, {} kept",
+        taken, SAMPLE_HZ, kept
+    );
+    let mut chunk = [0; CHUNK];
+    for start in (0..kept).step_by(CHUNK) {
+        let len = CHUNK.min(kept - start);
+        for (i, pc) in chunk[..len].iter_mut().enumerate() {
+            *pc = RING[start + i].load(Ordering::Relaxed);
+        }
+        info!("profile samples {=[u32]:#x}", &chunk[..len]);
+    }
+    info!("profile end");
+}
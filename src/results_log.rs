@@ -0,0 +1,258 @@
+//! The results log: a ring of fixed-size slots in the 16K of flash that
+//! `build.rs` keeps back from the application, so the answers of past runs
+//! outlive the RTT session and power cycles. Slots are filled in turn across
+//! every sector and a sector is only erased when the ring comes round to it
+//! again, spreading the wear evenly.
+//!
+//! Each slot holds a sequence number, an encoded [`Record`], then the
+//! sequence number inverted. That is written last, so a slot torn by a reset
+//! part way through a write is ignored.
+//!
+//! Once the days have run, [`serve`] makes the log readable over USB serial.
+
+use core::fmt::Write as _;
+use core::ptr::addr_of;
+
+use arrayvec::ArrayString;
+use embassy_futures::join::join;
+use embassy_rp::bind_interrupts;
+use embassy_rp::flash::{Blocking, Error, Flash, ERASE_SIZE};
+use embassy_rp::peripherals::{FLASH, USB};
+use embassy_rp::usb::{Driver, InterruptHandler};
+use embassy_usb::class::cdc_acm::{CdcAcmClass, State};
+use embassy_usb::driver::EndpointError;
+use embassy_usb::{Builder, Config};
+
+use crate::aoc::results::{Record, ResultsLog};
+use crate::error;
+
+const FLASH_SIZE: usize = 2 * 1024 * 1024;
+const FLASH_BASE: usize = 0x1000_0000;
+const SLOT_SIZE: usize = 128;
+const SLOTS_PER_SECTOR: usize = ERASE_SIZE / SLOT_SIZE;
+/// Where the inverted sequence number goes.
+const CHECK: usize = 4 + Record::SIZE;
+const _: () = assert!(CHECK + 4 <= SLOT_SIZE);
+
+extern "C" {
+    static __results_log_start: u8;
+    static __results_log_end: u8;
+}
+
+bind_interrupts!(struct Irqs {
+    USBCTRL_IRQ => InterruptHandler<USB>;
+});
+
+pub struct FlashLog {
+    flash: Flash<'static, FLASH, Blocking, FLASH_SIZE>,
+    /// Offset of the first slot from the start of flash
+    start: u32,
+    slots: usize,
+    /// The slot the next record goes in
+    next: usize,
+    /// The sequence number of the next record
+    seq: u32,
+}
+
+impl FlashLog {
+    /// Opens the log, to carry on after its newest record.
+    pub fn new(flash: FLASH) -> Self {
+        let (start, end) = unsafe {
+            (
+                addr_of!(__results_log_start) as usize,
+                addr_of!(__results_log_end) as usize,
+            )
+        };
+        let mut log = Self {
+            flash: Flash::new_blocking(flash),
+            start: (start - FLASH_BASE) as u32,
+            slots: (end - start) / SLOT_SIZE,
+            next: 0,
+            seq: 0,
+        };
+        let mut newest = None;
+        for slot in 0..log.slots {
+            if let Some((seq, _)) = log.read(slot) {
+                if newest.map_or(true, |(newest, _)| seq > newest) {
+                    newest = Some((seq, slot));
+                }
+            }
+        }
+        if let Some((seq, slot)) = newest {
+            log.seq = seq + 1;
+            log.next = (slot + 1) % log.slots;
+        }
+        log
+    }
+
+    /// How many records the log holds when full.
+    pub fn slots(&self) -> usize {
+        self.slots
+    }
+
+    /// The `index`th oldest slot's sequence number and record, if it holds
+    /// one.
+    pub fn oldest(&mut self, index: usize) -> Option<(u32, Record)> {
+        self.read((self.next + index) % self.slots)
+    }
+
+    pub fn erase(&mut self) -> Result<(), Error> {
+        let end = self.start + (self.slots * SLOT_SIZE) as u32;
+        self.flash.blocking_erase(self.start, end)?;
+        self.next = 0;
+        self.seq = 0;
+        Ok(())
+    }
+
+    fn offset(&self, slot: usize) -> u32 {
+        self.start + (slot * SLOT_SIZE) as u32
+    }
+
+    fn read_slot(&mut self, slot: usize) -> [u8; SLOT_SIZE] {
+        let mut bytes = [0xFF; SLOT_SIZE];
+        if let Err(e) = self.flash.blocking_read(self.offset(slot), &mut bytes) {
+            error!("results log read failed: {}", e);
+        }
+        bytes
+    }
+
+    fn read(&mut self, slot: usize) -> Option<(u32, Record)> {
+        let bytes = self.read_slot(slot);
+        let seq = u32::from_le_bytes(bytes[..4].try_into().unwrap());
+        let check = u32::from_le_bytes(bytes[CHECK..CHECK + 4].try_into().unwrap());
+        if check != !seq {
+            return None;
+        }
+        Record::decode(bytes[4..CHECK].try_into().unwrap()).map(|record| (seq, record))
+    }
+}
+
+impl ResultsLog for FlashLog {
+    fn append(&mut self, record: &Record) {
+        // Flash bits only clear until erased, so pass over slots a reset left
+        // part written, as far as the next sector, which is erased anyway
+        while self.next % SLOTS_PER_SECTOR != 0 && self.read_slot(self.next) != [0xFF; SLOT_SIZE] {
+            self.next = (self.next + 1) % self.slots;
+        }
+        let offset = self.offset(self.next);
+        if self.next % SLOTS_PER_SECTOR == 0 {
+            if let Err(e) = self
+                .flash
+                .blocking_erase(offset, offset + ERASE_SIZE as u32)
+            {
+                error!("results log erase failed: {}", e);
+                return;
+            }
+        }
+
+        let mut bytes = [0xFF; SLOT_SIZE];
+        bytes[..4].copy_from_slice(&self.seq.to_le_bytes());
+        bytes[4..CHECK].copy_from_slice(&record.encode());
+        bytes[CHECK..CHECK + 4].copy_from_slice(&(!self.seq).to_le_bytes());
+        if let Err(e) = self.flash.blocking_write(offset, &bytes) {
+            error!("results log write failed: {}", e);
+            return;
+        }
+        self.seq += 1;
+        self.next = (self.next + 1) % self.slots;
+    }
+}
+
+/// Serves `log` over USB serial, one command per line: `dump` lists every
+/// record, oldest first, and `erase` empties the log.
+pub async fn serve(usb: USB, log: &mut FlashLog) -> ! {
+    let mut config = Config::new(0xc0de, 0xcafe);
+    config.manufacturer = Some("advent_2024_embassy");
+    config.product = Some("results log");
+    config.max_power = 100;
+    config.max_packet_size_0 = 64;
+
+    let mut device_descriptor = [0; 256];
+    let mut config_descriptor = [0; 256];
+    let mut bos_descriptor = [0; 256];
+    let mut control_buf = [0; 64];
+    let mut state = State::new();
+    let mut builder = Builder::new(
+        Driver::new(usb, Irqs),
+        config,
+        &mut device_descriptor,
+        &mut config_descriptor,
+        &mut bos_descriptor,
+        &mut [], // no msos descriptors
+        &mut control_buf,
+    );
+    let mut class = CdcAcmClass::new(&mut builder, &mut state, 64);
+    let mut usb = builder.build();
+
+    let commands = async {
+        loop {
+            class.wait_connection().await;
+            // Unplugged; wait for the next connection
+            let _ = session(&mut class, log).await;
+        }
+    };
+    join(usb.run(), commands).await.0
+}
+
+async fn session(
+    class: &mut CdcAcmClass<'_, Driver<'_, USB>>,
+    log: &mut FlashLog,
+) -> Result<(), EndpointError> {
+    let mut line = ArrayString::<16>::new();
+    let mut packet = [0; 64];
+    loop {
+        let len = class.read_packet(&mut packet).await?;
+        for &byte in &packet[..len] {
+            match byte {
+                b'\r' | b'\n' if !line.is_empty() => {
+                    command(class, log, line.trim()).await?;
+                    line.clear();
+                }
+                b'\r' | b'\n' => {}
+                // A line too long for any command is cut short, and rejected
+                _ => {
+                    let _ = line.try_push(byte as char);
+                }
+            }
+        }
+    }
+}
+
+async fn command(
+    class: &mut CdcAcmClass<'_, Driver<'_, USB>>,
+    log: &mut FlashLog,
+    command: &str,
+) -> Result<(), EndpointError> {
+    match command {
+        "dump" => {
+            for index in 0..log.slots() {
+                if let Some((seq, record)) = log.oldest(index) {
+                    let mut text = ArrayString::<192>::new();
+                    let _ = write!(text, "{} {}\r\n", seq, record);
+                    send(class, &text).await?;
+                }
+            }
+            send(class, "end\r\n").await
+        }
+        "erase" => match log.erase() {
+            Ok(()) => send(class, "erased\r\n").await,
+            Err(e) => {
+                error!("results log erase failed: {}", e);
+                send(class, "erase failed\r\n").await
+            }
+        },
+        _ => send(class, "commands: dump, erase\r\n").await,
+    }
+}
+
+/// Sends `text` in short packets, so the host never waits on a zero-length
+/// one to end a transfer.
+async fn send(
+    class: &mut CdcAcmClass<'_, Driver<'_, USB>>,
+    text: &str,
+) -> Result<(), EndpointError> {
+    for chunk in text.as_bytes().chunks(63) {
+        class.write_packet(chunk).await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,87 @@
+//! Time budgets for the days, kept by the RP2040 watchdog. The days never
+//! yield to an executor, so [`guard`] runs on the LED's, above them, and
+//! feeds the watchdog only while the running day is within its budget and
+//! has passed a [`status::checkpoint`] since the last feed. A day that overruns
+//! or gets stuck is left to the watchdog, which resets the board.
+//!
+//! While a day runs, its number is kept in the watchdog's scratch registers,
+//! which survive the reset, so that the next boot can report it with
+//! [`take_timed_out`] and leave it out.
+
+use embassy_rp::watchdog::{ResetReason, Watchdog};
+use embassy_time::{Duration, Instant, Timer};
+
+use crate::aoc::results::InputKind;
+use crate::aoc::status::{self, Phase};
+
+/// Longest a day may go between checkpoints; the watchdog's own limit is
+/// about 8s.
+const PERIOD: Duration = Duration::from_secs(4);
+/// How often the watchdog is fed, given progress.
+const POLL: Duration = Duration::from_millis(500);
+/// Marks the scratch registers as holding a running day, rather than whatever
+/// they powered up as. Scratch 4 to 7 belong to the bootrom.
+const MAGIC: u32 = 0x7143_D06E;
+
+/// A day that the watchdog reset part way through.
+pub struct TimedOut {
+    pub day: u8,
+    pub input: InputKind,
+    pub budget_ms: u32,
+}
+
+/// The day the last run timed out on, if it did. Each is only reported once.
+pub fn take_timed_out(watchdog: &mut Watchdog) -> Option<TimedOut> {
+    let running = watchdog.get_scratch(0) == MAGIC;
+    watchdog.set_scratch(0, 0);
+    if !running || watchdog.reset_reason() != Some(ResetReason::TimedOut) {
+        return None;
+    }
+    let day = watchdog.get_scratch(1);
+    Some(TimedOut {
+        day: day as u8,
+        input: InputKind::try_from((day >> 8) as u8).ok()?,
+        budget_ms: watchdog.get_scratch(2),
+    })
+}
+
+/// Keeps the days to their budgets, forever.
+pub async fn guard(mut watchdog: Watchdog) -> ! {
+    watchdog.pause_on_debug(true);
+    watchdog.start(PERIOD);
+    let mut running = None;
+    let mut fed = status::progress();
+    loop {
+        match status::phase() {
+            // A panic stops the days for good; leave the LED showing it
+            Phase::Idle | Phase::Panicked => {
+                if running.take().is_some() {
+                    watchdog.set_scratch(0, 0);
+                }
+                watchdog.feed();
+            }
+            Phase::Parsing | Phase::Solving => {
+                let day = status::day();
+                // The budget covers all the day's inputs; note the current one
+                watchdog.set_scratch(1, day as u32 | (status::input() as u32) << 8);
+                let started = match running {
+                    Some((running_day, started)) if running_day == day => started,
+                    _ => {
+                        let now = Instant::now();
+                        running = Some((day, now));
+                        watchdog.set_scratch(2, status::budget_ms());
+                        watchdog.set_scratch(0, MAGIC);
+                        now
+                    }
+                };
+                let budget = Duration::from_millis(status::budget_ms() as u64);
+                let progress = status::progress();
+                if progress != fed && started.elapsed() < budget {
+                    fed = progress;
+                    watchdog.feed();
+                }
+            }
+        }
+        Timer::after(POLL).await;
+    }
+}
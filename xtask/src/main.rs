@@ -1,11 +1,13 @@
-use anyhow::{Context, Result as AnyResult};
-use std::{env, path::PathBuf};
+use anyhow::{Context, Result as AnyResult, bail, ensure};
+use std::{collections::HashMap, env, path::PathBuf, process::Command};
 
 fn main() -> AnyResult<()> {
     eprintln!("=-=-=-=-= No documentation as-yet for 'setup'");
     let task = env::args().nth(1);
     match task.as_deref() {
         Some("setup") => setup(),
+        Some("flash-firmware") => flash_firmware(),
+        Some("profile") => profile(),
         _ => xtaskops::tasks::main(),
     }
 }
@@ -67,3 +69,190 @@ fn setup() -> AnyResult<()> {
     // std::fs::write(launcher_toml_path, toml.to_string())?;
     Ok(())
 }
+
+/// Flash partitions for the cyw43 blobs, matching `build.rs` and
+/// `src/firmware.rs`: (blob, base address, partition size).
+const FIRMWARE_PARTITIONS: [(&str, u32, usize); 2] = [
+    ("43439A0.bin", 0x1010_0000, 256 * 1024),
+    ("43439A0_clm.bin", 0x1014_0000, 16 * 1024),
+];
+
+/// Writes the cyw43 blobs, each behind the header `src/firmware.rs` checks,
+/// to their own partitions. Only needed once per board, or when the
+/// firmware changes.
+fn flash_firmware() -> AnyResult<()> {
+    let out = PathBuf::from("target").join("cyw43");
+    std::fs::create_dir_all(&out)?;
+    for (name, base, size) in FIRMWARE_PARTITIONS {
+        let blob = std::fs::read(PathBuf::from("cyw43-firmware").join(name))
+            .with_context(|| format!("read {name}"))?;
+        let mut image = Vec::with_capacity(12 + blob.len());
+        image.extend_from_slice(b"CYW4");
+        image.extend_from_slice(&u32::try_from(blob.len())?.to_le_bytes());
+        image.extend_from_slice(&fnv1a(&blob).to_le_bytes());
+        image.extend_from_slice(&blob);
+        ensure!(
+            image.len() <= size,
+            "{name} needs {} bytes, partition holds {size}",
+            image.len()
+        );
+
+        let path = out.join(name);
+        std::fs::write(&path, &image)?;
+        println!("* flash {name} ({} bytes) at {base:#x}", blob.len());
+        let status = Command::new("probe-rs")
+            .arg("download")
+            .arg(&path)
+            .args(["--format", "bin", "--chip", "RP2040", "--base-address"])
+            .arg(format!("{base:#x}"))
+            .status()
+            .context("run probe-rs")?;
+        if !status.success() {
+            bail!("probe-rs failed flashing {name}: {status}");
+        }
+    }
+    Ok(())
+}
+
+fn fnv1a(data: &[u8]) -> u32 {
+    data.iter().fold(0x811c_9dc5, |hash, byte| {
+        (hash ^ *byte as u32).wrapping_mul(0x0100_0193)
+    })
+}
+
+/// Turns the samples a `profile` build logs into a flat per-function
+/// profile, e.g. after `cargo run --release --features profile | tee run.log`
+/// run `cargo xtask profile run.log`. Addresses are matched against
+/// `linker.map`, or the map given after the log, from the same build.
+fn profile() -> AnyResult<()> {
+    let log = env::args().nth(2).context("require the run's log")?;
+    let map = env::args().nth(3).unwrap_or_else(|| "linker.map".into());
+    let samples = std::fs::read_to_string(&log)
+        .with_context(|| format!("read {log}"))?
+        .lines()
+        .filter_map(|line| line.split_once("profile samples [")?.1.split_once(']'))
+        .flat_map(|(samples, _)| samples.split(','))
+        .map(|pc| u32::from_str_radix(pc.trim().trim_start_matches("0x"), 16))
+        .collect::<Result<Vec<_>, _>>()
+        .context("parse profile samples")?;
+    ensure!(
+        !samples.is_empty(),
+        "no profile samples in {log}; build with --features profile"
+    );
+    let (functions, text_end) =
+        functions(&std::fs::read_to_string(&map).with_context(|| format!("read {map}"))?);
+
+    let mut counts = HashMap::<&str, usize>::new();
+    for pc in samples.iter() {
+        let index = functions.partition_point(|(start, _)| start <= pc);
+        let name = match index.checked_sub(1) {
+            Some(index) if *pc < text_end => functions[index].1.as_str(),
+            _ => "<outside .text>",
+        };
+        *counts.entry(name).or_default() += 1;
+    }
+    let mut counts = counts.into_iter().collect::<Vec<_>>();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    println!("{:>7} {:>7}  function", "samples", "%");
+    for (name, count) in counts {
+        let percent = 100.0 * count as f64 / samples.len() as f64;
+        println!("{count:>7} {percent:>6.2}%  {name}");
+    }
+    Ok(())
+}
+
+/// The functions in `map`, as lld writes it, by start address, and the end
+/// of `.text`. Rows give VMA, LMA, size and alignment, then an output
+/// section, an input section (`file:(section)`) or a symbol.
+fn functions(map: &str) -> (Vec<(u32, String)>, u32) {
+    let mut functions = Vec::new();
+    let mut in_text = false;
+    let mut text_end = 0;
+    for line in map.lines() {
+        let mut fields = line.split_whitespace();
+        let (Some(vma), Some(_lma), Some(size), Some(_align), Some(name), None) = (
+            fields.next(),
+            fields.next(),
+            fields.next(),
+            fields.next(),
+            fields.next(),
+            fields.next(),
+        ) else {
+            continue;
+        };
+        let (Ok(vma), Ok(size)) = (u32::from_str_radix(vma, 16), u32::from_str_radix(size, 16))
+        else {
+            continue;
+        };
+        if name.contains(":(") {
+            continue;
+        }
+        if name.starts_with('.') {
+            in_text = name == ".text";
+            if in_text {
+                text_end = vma + size;
+            }
+        } else if in_text {
+            functions.push((vma, demangle(name)));
+        }
+    }
+    functions.sort();
+    (functions, text_end)
+}
+
+/// Demangles a legacy Rust symbol, `_ZN` then length-prefixed path segments
+/// and a hash, then `E`; anything else is left as it is.
+fn demangle(symbol: &str) -> String {
+    let Some(mut rest) = symbol.strip_prefix("_ZN") else {
+        return symbol.to_string();
+    };
+    let mut segments = Vec::new();
+    while let Some(digits) = rest.find(|c: char| !c.is_ascii_digit()).filter(|d| *d > 0) {
+        let Ok(len) = rest[..digits].parse::<usize>() else {
+            return symbol.to_string();
+        };
+        let Some(segment) = rest.get(digits..digits + len) else {
+            return symbol.to_string();
+        };
+        // A segment may not start with `$`, so one that would has a `_` first
+        segments.push(
+            segment
+                .strip_prefix('_')
+                .filter(|s| s.starts_with('$'))
+                .unwrap_or(segment),
+        );
+        rest = &rest[digits + len..];
+    }
+    if rest != "E" {
+        return symbol.to_string();
+    }
+    if segments
+        .last()
+        .is_some_and(|hash| hash.len() == 17 && hash.starts_with('h'))
+    {
+        segments.pop();
+    }
+    let mut name = segments.join("::");
+    for (escape, c) in [
+        ("$LT$", "<"),
+        ("$GT$", ">"),
+        ("$RF$", "&"),
+        ("$BP$", "*"),
+        ("$C$", ","),
+        ("$SP$", "@"),
+        ("$u20$", " "),
+        ("$u22$", "\""),
+        ("$u27$", "'"),
+        ("$u2b$", "+"),
+        ("$u3b$", ";"),
+        ("$u5b$", "["),
+        ("$u5d$", "]"),
+        ("$u7b$", "{"),
+        ("$u7d$", "}"),
+        ("$u7e$", "~"),
+        ("..", "::"),
+    ] {
+        name = name.replace(escape, c);
+    }
+    name
+}